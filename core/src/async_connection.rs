@@ -0,0 +1,125 @@
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+use tokio::sync::oneshot;
+
+use crate::{Connection, Error};
+use crate::execute::{BoundExecute, ExecuteResult};
+use crate::from_row::FromRow;
+use crate::insert::{Insert, InsertRecord};
+use crate::query::BoundQuery;
+use crate::types::{Column, ColumnCons, ColumnList};
+use crate::types::param::{BindParams, ParamList};
+
+type Call = Box<dyn FnOnce(&mut Connection) + Send>;
+
+/// Handle to a connection owned by a dedicated thread, which runs queries
+/// sent to it in turn so they don't block the async runtime. Clones of the
+/// handle share the connection, which is closed once all are dropped.
+#[derive(Clone)]
+pub struct AsyncConnection {
+    calls: mpsc::Sender<Call>,
+}
+
+impl AsyncConnection {
+    /// Moves the connection onto a new thread
+    pub fn new(conn: Connection) -> Self {
+        let (calls, rx) = mpsc::channel::<Call>();
+
+        thread::spawn(move || {
+            let mut conn = conn;
+
+            for call in rx {
+                call(&mut conn);
+            }
+        });
+
+        AsyncConnection { calls }
+    }
+
+    /// Opens the database at `path` on a new thread
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_owned();
+        let (tx, rx) = oneshot::channel();
+
+        thread::spawn(move || {
+            let _ = tx.send(Connection::open(path));
+        });
+
+        let conn = rx.await.map_err(|_| Error::ConnectionClosed)??;
+        Ok(AsyncConnection::new(conn))
+    }
+
+    /// Runs `f` with the connection on its thread, returning its result
+    pub async fn call<R, F>(&self, f: F) -> Result<R, Error>
+        where
+            R: Send + 'static,
+            F: FnOnce(&mut Connection) -> Result<R, Error> + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.calls.send(Box::new(move |conn| { let _ = tx.send(f(conn)); }))
+            .map_err(|_| Error::ConnectionClosed)?;
+
+        rx.await.map_err(|_| Error::ConnectionClosed)?
+    }
+}
+
+impl<Row, Params, B> BoundQuery<Row, Params, B>
+    where
+        Row: ColumnList + Send + 'static,
+        Params: ParamList + Send + 'static,
+        B: BindParams<Params> + Send + 'static,
+{
+    pub async fn query_all_async<T>(self, conn: &AsyncConnection) -> Result<Vec<T>, Error>
+        where T: FromRow<Row> + Send + 'static
+    {
+        conn.call(move |conn| self.query_all(conn)).await
+    }
+
+    pub async fn query_one_async<T>(self, conn: &AsyncConnection) -> Result<T, Error>
+        where T: FromRow<Row> + Send + 'static
+    {
+        conn.call(move |conn| self.query_one(conn)).await
+    }
+
+    pub async fn query_optional_async<T>(self, conn: &AsyncConnection) -> Result<Option<T>, Error>
+        where T: FromRow<Row> + Send + 'static
+    {
+        conn.call(move |conn| self.query_optional(conn)).await
+    }
+}
+
+impl<Col, Params, B> BoundQuery<ColumnCons<Col, ()>, Params, B>
+    where
+        Col: Column + Send + 'static,
+        Col::DomainType: Send,
+        Params: ParamList + Send + 'static,
+        B: BindParams<Params> + Send + 'static,
+{
+    pub async fn query_scalar_async(self, conn: &AsyncConnection) -> Result<Col::DomainType, Error> {
+        conn.call(move |conn| self.query_scalar(conn)).await
+    }
+}
+
+impl<Params, B> BoundExecute<Params, B>
+    where
+        Params: ParamList + Send + 'static,
+        B: BindParams<Params> + Send + 'static,
+{
+    pub async fn execute_async(self, conn: &AsyncConnection) -> Result<ExecuteResult, Error> {
+        conn.call(move |conn| self.execute(conn)).await
+    }
+}
+
+impl<Record> Insert<Record>
+    where
+        Record: InsertRecord + Send + 'static,
+        Record::Values: BindParams<Record::Params>,
+        Record::Output: Send + 'static,
+{
+    pub async fn execute_async(self, conn: &AsyncConnection) -> Result<Record::Output, Error> {
+        conn.call(move |conn| self.execute(conn)).await
+    }
+}
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("query returned no rows")]
+    NoRows,
+    #[error("query returned more than one row")]
+    TooManyRows,
+    /// The thread of an `AsyncConnection`, with the `tokio` feature, has
+    /// exited. Defined regardless, so enabling the feature doesn't break
+    /// exhaustive matches.
+    #[error("connection has been closed")]
+    ConnectionClosed,
+}
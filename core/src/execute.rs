@@ -0,0 +1,87 @@
+use std::marker::PhantomData;
+
+use rusqlite::CachedStatement;
+
+use crate::{Connection, Error};
+use crate::executor::Executor;
+use crate::types::param::{BindParams, ParamList};
+
+/// A statement which modifies the database and returns no rows, such as an
+/// INSERT, UPDATE or DELETE without a RETURNING clause
+pub struct Execute<Params> {
+    sql: &'static str,
+    _phantom: PhantomData<Params>,
+}
+
+impl<Params> Clone for Execute<Params> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Params> Copy for Execute<Params> {}
+
+impl<Params: ParamList> Execute<Params> {
+    pub const fn new_unchecked(sql: &'static str) -> Self {
+        Execute {
+            sql,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn sql(&self) -> &'static str {
+        self.sql
+    }
+
+    pub fn bind<B: BindParams<Params>>(&self, params: B) -> BoundExecute<Params, B> {
+        BoundExecute { execute: *self, params }
+    }
+
+    /// Prepares the statement through the connection's statement cache, see
+    /// [`Query::prepare`](crate::Query::prepare)
+    pub fn prepare<'conn>(&self, conn: &'conn Connection) -> Result<PreparedExecute<'conn, Params>, Error> {
+        let stmt = conn.prepare_cached(self.sql)?;
+        Ok(PreparedExecute { conn, stmt, _phantom: PhantomData })
+    }
+
+    pub fn warm(&self, conn: &Connection) -> Result<(), Error> {
+        self.prepare(conn).map(drop)
+    }
+}
+
+pub struct BoundExecute<Params, B> {
+    execute: Execute<Params>,
+    params: B,
+}
+
+impl<Params: ParamList, B: BindParams<Params>> BoundExecute<Params, B> {
+    pub fn execute(self, conn: &mut impl Executor) -> Result<ExecuteResult, Error> {
+        self.execute.prepare(conn.connection())?.execute(self.params)
+    }
+}
+
+pub struct PreparedExecute<'conn, Params> {
+    conn: &'conn Connection,
+    stmt: CachedStatement<'conn>,
+    _phantom: PhantomData<Params>,
+}
+
+impl<'conn, Params: ParamList> PreparedExecute<'conn, Params> {
+    pub fn execute<B: BindParams<Params>>(&mut self, params: B) -> Result<ExecuteResult, Error> {
+        params.bind_params(&mut self.stmt)?;
+        let rows_affected = self.stmt.raw_execute()?;
+
+        Ok(ExecuteResult {
+            rows_affected,
+            last_insert_rowid: self.conn.last_insert_rowid(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecuteResult {
+    pub rows_affected: usize,
+    /// Rowid of the most recent successful INSERT on the connection, which
+    /// may predate this statement if it inserted no rows
+    pub last_insert_rowid: i64,
+}
@@ -0,0 +1,25 @@
+use crate::Connection;
+
+/// Implemented by everything typed queries, executes and inserts can be run
+/// on: connections, and transactions or savepoints on them
+pub trait Executor {
+    fn connection(&self) -> &Connection;
+}
+
+impl Executor for Connection {
+    fn connection(&self) -> &Connection {
+        self
+    }
+}
+
+impl Executor for rusqlite::Transaction<'_> {
+    fn connection(&self) -> &Connection {
+        self
+    }
+}
+
+impl Executor for rusqlite::Savepoint<'_> {
+    fn connection(&self) -> &Connection {
+        self
+    }
+}
@@ -100,6 +100,49 @@ impl FromRow<()> for () {
     }
 }
 
+/// Implemented by `#[derive(FromRow)]`, giving the names of the fields read
+/// from each column of a row, in order
+pub trait FromRowFields {
+    const FIELDS: &'static [&'static str];
+}
+
+/// Checks at compile time that the fields of `T` are named the same as the
+/// result columns of a query, in the same order. Used by `query_as!`.
+#[doc(hidden)]
+pub const fn assert_fields<T: FromRowFields>(columns: &[&str]) {
+    let fields = T::FIELDS;
+
+    if fields.len() != columns.len() {
+        panic!("struct fields do not match query result columns");
+    }
+
+    let mut i = 0;
+    while i < fields.len() {
+        if !str_eq(fields[i], columns[i]) {
+            panic!("struct fields are not in the same order as query result columns");
+        }
+        i += 1;
+    }
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
 macro_rules! __make_column_cons {
     ( ( $col:ident, $($rest:ident,)* ) ) => {
         ColumnCons< $col, __make_column_cons!{ ( $($rest,)* ) } >
@@ -143,3 +186,31 @@ impl_from_row_for_tuple!{ (t1: C1, t2: C2, t3: C3, t4: C4, t5: C5, t6: C6, t7: C
 impl_from_row_for_tuple!{ (t1: C1, t2: C2, t3: C3, t4: C4, t5: C5, t6: C6, t7: C7, t8: C8, t9: C9, t10: C10, t11: C11, t12: C12, t13: C13, t14: C14,) }
 impl_from_row_for_tuple!{ (t1: C1, t2: C2, t3: C3, t4: C4, t5: C5, t6: C6, t7: C7, t8: C8, t9: C9, t10: C10, t11: C11, t12: C12, t13: C13, t14: C14, t15: C15,) }
 impl_from_row_for_tuple!{ (t1: C1, t2: C2, t3: C3, t4: C4, t5: C5, t6: C6, t7: C7, t8: C8, t9: C9, t10: C10, t11: C11, t12: C12, t13: C13, t14: C14, t15: C15, t16: C16,) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Point;
+
+    impl FromRowFields for Point {
+        const FIELDS: &'static [&'static str] = &["x", "y"];
+    }
+
+    #[test]
+    fn fields_matching_columns() {
+        assert_fields::<Point>(&["x", "y"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "struct fields do not match query result columns")]
+    fn fields_missing_a_column() {
+        assert_fields::<Point>(&["x", "y", "z"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "struct fields are not in the same order as query result columns")]
+    fn fields_out_of_order() {
+        assert_fields::<Point>(&["y", "x"]);
+    }
+}
@@ -1,16 +1,57 @@
-use rusqlite::Error;
+use crate::{Connection, Error};
+use crate::executor::Executor;
+use crate::types::param::{BindParams, ParamList};
+
+/// Implemented by the record structs `schema!` generates for inserting into
+/// a table, converting a record into the values of its columns.
+pub trait InsertRecord {
+    /// Parameters of the insert statement, one per column inserted into
+    type Params: ParamList;
+    type Values;
+    /// Returned by inserting the record, see [`InsertOutput`]
+    type Output: InsertOutput;
+
+    fn into_values(self) -> Self::Values;
+}
+
+/// Result of inserting a record, read from the connection once inserted
+pub trait InsertOutput {
+    fn from_insert(conn: &Connection) -> Self;
+}
+
+/// The rowid assigned to the record
+impl InsertOutput for i64 {
+    fn from_insert(conn: &Connection) -> Self {
+        conn.last_insert_rowid()
+    }
+}
+
+/// Records of WITHOUT ROWID tables are assigned no rowid, so nothing is
+/// returned for them
+impl InsertOutput for () {
+    fn from_insert(_: &Connection) -> Self {}
+}
 
 pub struct Insert<Record> {
     sql: &'static str,
-    record: Vec<Record>,
+    record: Record,
 }
 
-impl<Record> Insert<Record> {
+impl<Record: InsertRecord> Insert<Record> {
     pub fn new_unchecked(sql: &'static str, record: Record) -> Self {
         Insert { sql, record }
     }
 
-    pub fn execute(self, conn: &mut Connection) -> Result<(), Error> {}
-}
+    /// Inserts the record, returning its rowid, or nothing for tables
+    /// WITHOUT ROWID
+    pub fn execute(self, conn: &mut impl Executor) -> Result<Record::Output, Error>
+        where Record::Values: BindParams<Record::Params>
+    {
+        let conn = conn.connection();
+        let mut stmt = conn.prepare_cached(self.sql)?;
+        self.record.into_values().bind_params(&mut stmt)?;
+        stmt.raw_execute()?;
 
-// impl<Record> Insert<Record> where Record::Table: Rowid
+        Ok(Record::Output::from_insert(conn))
+    }
+}
@@ -1,11 +1,36 @@
-pub use sqlitemapper_macros::{query, schema};
-pub use rusqlite::{Params, Connection, Error};
+pub use sqlitemapper_macros::{query, query_as, schema, FromRow};
+pub use rusqlite::{self, Connection};
+
+pub mod error;
+pub use error::Error;
 
 pub mod types;
 
 pub mod from_row;
+pub use from_row::FromRow;
 
 pub mod query;
 pub use query::Query;
 
-// pub mod insert;
+pub mod execute;
+pub use execute::Execute;
+
+pub mod insert;
+
+pub mod executor;
+pub use executor::Executor;
+
+pub mod transaction;
+pub use transaction::{transaction, Transaction, TransactionBehavior};
+
+pub mod verify;
+
+#[cfg(feature = "pool")]
+pub mod pool;
+#[cfg(feature = "pool")]
+pub use pool::Pool;
+
+#[cfg(feature = "tokio")]
+pub mod async_connection;
+#[cfg(feature = "tokio")]
+pub use async_connection::AsyncConnection;
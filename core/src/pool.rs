@@ -0,0 +1,233 @@
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+
+use rusqlite::OpenFlags;
+
+use crate::{Connection, Error};
+use crate::executor::Executor;
+
+type InitHook = Box<dyn Fn(&mut Connection) -> rusqlite::Result<()> + Send + Sync>;
+
+/// Pool of connections to a database file in WAL mode, split into a single
+/// writer and any number of read-only readers, which WAL lets run alongside
+/// the writer.
+///
+/// Checked out connections are [`Executor`]s, so typed queries run on them
+/// directly.
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    path: PathBuf,
+    init_hooks: Vec<InitHook>,
+    writer: Slots,
+    readers: Slots,
+}
+
+/// Connections of one kind, opened lazily up to `max`
+struct Slots {
+    max: usize,
+    flags: OpenFlags,
+    state: Mutex<SlotState>,
+    available: Condvar,
+}
+
+struct SlotState {
+    idle: Vec<Connection>,
+    open: usize,
+}
+
+pub struct PoolBuilder {
+    path: PathBuf,
+    max_readers: usize,
+    init_hooks: Vec<InitHook>,
+}
+
+impl PoolBuilder {
+    /// Sets the most read-only connections open at once, 4 by default
+    pub fn max_readers(mut self, max_readers: usize) -> Self {
+        self.max_readers = max_readers.max(1);
+        self
+    }
+
+    /// Adds a hook run on each connection when it's opened, eg. to set
+    /// pragmas or register functions. Hooks run in the order they're added.
+    pub fn init<F>(mut self, hook: F) -> Self
+        where F: Fn(&mut Connection) -> rusqlite::Result<()> + Send + Sync + 'static
+    {
+        self.init_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Opens the pool, creating the database if needed and switching it to
+    /// WAL mode through the writer connection
+    pub fn open(self) -> Result<Pool, Error> {
+        let pool = Pool {
+            inner: Arc::new(Inner {
+                path: self.path,
+                init_hooks: self.init_hooks,
+                writer: Slots::new(1, OpenFlags::default()),
+                readers: Slots::new(self.max_readers, read_only_flags()),
+            }),
+        };
+
+        let writer = pool.write()?;
+        writer.pragma_update(None, "journal_mode", "WAL")?;
+        drop(writer);
+
+        Ok(pool)
+    }
+}
+
+impl Pool {
+    pub fn builder(path: impl AsRef<Path>) -> PoolBuilder {
+        PoolBuilder {
+            path: path.as_ref().to_owned(),
+            max_readers: 4,
+            init_hooks: Vec::new(),
+        }
+    }
+
+    /// Opens a pool with the default settings
+    pub fn open(path: impl AsRef<Path>) -> Result<Pool, Error> {
+        Pool::builder(path).open()
+    }
+
+    /// Checks out a read-only connection, waiting for one to be returned if
+    /// all are in use
+    pub fn read(&self) -> Result<PooledConnection, Error> {
+        self.checkout(Kind::Read)
+    }
+
+    /// Checks out the writer connection, waiting for it to be returned if
+    /// in use
+    pub fn write(&self) -> Result<PooledConnection, Error> {
+        self.checkout(Kind::Write)
+    }
+
+    fn checkout(&self, kind: Kind) -> Result<PooledConnection, Error> {
+        let conn = self.inner.slots(kind).checkout(|flags| self.inner.connect(flags))?;
+
+        Ok(PooledConnection {
+            conn: Some(conn),
+            pool: self.inner.clone(),
+            kind,
+        })
+    }
+}
+
+impl Inner {
+    fn slots(&self, kind: Kind) -> &Slots {
+        match kind {
+            Kind::Read => &self.readers,
+            Kind::Write => &self.writer,
+        }
+    }
+
+    fn connect(&self, flags: OpenFlags) -> Result<Connection, Error> {
+        let mut conn = Connection::open_with_flags(&self.path, flags)?;
+
+        for hook in &self.init_hooks {
+            hook(&mut conn)?;
+        }
+
+        Ok(conn)
+    }
+}
+
+impl Slots {
+    fn new(max: usize, flags: OpenFlags) -> Self {
+        Slots {
+            max,
+            flags,
+            state: Mutex::new(SlotState { idle: Vec::new(), open: 0 }),
+            available: Condvar::new(),
+        }
+    }
+
+    fn checkout(&self, connect: impl FnOnce(OpenFlags) -> Result<Connection, Error>) -> Result<Connection, Error> {
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            if let Some(conn) = state.idle.pop() {
+                return Ok(conn);
+            }
+
+            if state.open < self.max {
+                break;
+            }
+
+            state = self.available.wait(state).unwrap();
+        }
+
+        // connect without holding the lock, reserving the slot meanwhile
+        state.open += 1;
+        drop(state);
+
+        connect(self.flags).inspect_err(|_| {
+            self.state.lock().unwrap().open -= 1;
+            self.available.notify_one();
+        })
+    }
+
+    fn checkin(&self, conn: Connection) {
+        self.state.lock().unwrap().idle.push(conn);
+        self.available.notify_one();
+    }
+}
+
+fn read_only_flags() -> OpenFlags {
+    (OpenFlags::default() - OpenFlags::SQLITE_OPEN_READ_WRITE - OpenFlags::SQLITE_OPEN_CREATE)
+        | OpenFlags::SQLITE_OPEN_READ_ONLY
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Read,
+    Write,
+}
+
+/// Connection checked out of a [`Pool`], returned to it when dropped
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    pool: Arc<Inner>,
+    kind: Kind,
+}
+
+impl PooledConnection {
+    /// Whether this is a read-only connection
+    pub fn is_read_only(&self) -> bool {
+        self.kind == Kind::Read
+    }
+}
+
+impl Executor for PooledConnection {
+    fn connection(&self) -> &Connection {
+        self
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.slots(self.kind).checkin(conn);
+        }
+    }
+}
@@ -1,48 +1,356 @@
 use std::marker::PhantomData;
 
-use crate::{Connection, Error, Params};
+use rusqlite::{CachedStatement, Rows};
+
+use crate::{Connection, Error};
+use crate::executor::Executor;
 use crate::from_row::{FromRow, RowReader};
-use crate::types::ColumnList;
+use crate::types::{Column, ColumnCons, ColumnList};
+use crate::types::param::{BindParams, ParamList};
 
-pub struct Query<Row> {
+pub struct Query<Row, Params> {
     sql: &'static str,
-    _phantom: PhantomData<Row>,
+    _phantom: PhantomData<(Row, Params)>,
 }
 
-impl<Row> Clone for Query<Row> {
+impl<Row, Params> Clone for Query<Row, Params> {
     fn clone(&self) -> Self {
-        Query { sql: self.sql, _phantom: PhantomData }
+        *self
     }
 }
 
-impl<Row> Copy for Query<Row> {}
+impl<Row, Params> Copy for Query<Row, Params> {}
 
-impl<Row: ColumnList> Query<Row> {
-    pub fn new_unchecked(sql: &'static str) -> Self {
+impl<Row: ColumnList, Params: ParamList> Query<Row, Params> {
+    pub const fn new_unchecked(sql: &'static str) -> Self {
         Query {
             sql,
             _phantom: PhantomData,
         }
     }
 
-    pub fn bind<P: Params>(&self, params: P) -> BoundQuery<Row, P> {
+    pub fn sql(&self) -> &'static str {
+        self.sql
+    }
+
+    pub fn bind<B: BindParams<Params>>(&self, params: B) -> BoundQuery<Row, Params, B> {
         BoundQuery { query: *self, params }
     }
+
+    /// Prepares the query through the connection's statement cache. The
+    /// statement is returned to the cache when the `PreparedQuery` is dropped.
+    pub fn prepare<'conn>(&self, conn: &'conn Connection) -> Result<PreparedQuery<'conn, Row, Params>, Error> {
+        let stmt = conn.prepare_cached(self.sql)?;
+        Ok(PreparedQuery { stmt, _phantom: PhantomData })
+    }
+
+    /// Prepares the query ahead of time so that later executions find it
+    /// already in the connection's statement cache.
+    pub fn warm(&self, conn: &Connection) -> Result<(), Error> {
+        self.prepare(conn).map(drop)
+    }
+}
+
+/// Prepares each of `queries` into the connection's statement cache. Note the
+/// cache only holds 16 statements unless its capacity is raised with
+/// [`Connection::set_prepared_statement_cache_capacity`].
+pub fn warm_cache<I>(conn: &Connection, queries: I) -> Result<(), Error>
+    where I: IntoIterator<Item = &'static str>
+{
+    for sql in queries {
+        conn.prepare_cached(sql)?;
+    }
+
+    Ok(())
+}
+
+pub struct BoundQuery<Row, Params, B> {
+    query: Query<Row, Params>,
+    params: B,
+}
+
+impl<Row: ColumnList, Params: ParamList, B: BindParams<Params>> BoundQuery<Row, Params, B> {
+    pub fn query_all<T: FromRow<Row>>(self, conn: &mut impl Executor) -> Result<Vec<T>, Error> {
+        self.query.prepare(conn.connection())?.query_all(self.params)
+    }
+
+    /// Returns the single row produced by the query, failing with
+    /// [`Error::NoRows`] or [`Error::TooManyRows`] otherwise.
+    pub fn query_one<T: FromRow<Row>>(self, conn: &mut impl Executor) -> Result<T, Error> {
+        self.query.prepare(conn.connection())?.query_one(self.params)
+    }
+
+    /// Like [`query_one`](Self::query_one), but returns `None` when the query
+    /// produces no rows.
+    pub fn query_optional<T: FromRow<Row>>(self, conn: &mut impl Executor) -> Result<Option<T>, Error> {
+        self.query.prepare(conn.connection())?.query_optional(self.params)
+    }
+
+    pub fn query_for_each<T, F>(self, conn: &mut impl Executor, f: F) -> Result<(), Error>
+        where T: FromRow<Row>, F: FnMut(T)
+    {
+        self.query.prepare(conn.connection())?.query_for_each(self.params, f)
+    }
+
+    pub fn query_fold<T, Acc, F>(self, conn: &mut impl Executor, init: Acc, f: F) -> Result<Acc, Error>
+        where T: FromRow<Row>, F: FnMut(Acc, T) -> Acc
+    {
+        self.query.prepare(conn.connection())?.query_fold(self.params, init, f)
+    }
+}
+
+impl<Col: Column, Params: ParamList, B: BindParams<Params>> BoundQuery<ColumnCons<Col, ()>, Params, B> {
+    /// Returns the value of the single column of the single row produced by
+    /// the query, eg. for `SELECT count(*) FROM ...`
+    pub fn query_scalar(self, conn: &mut impl Executor) -> Result<Col::DomainType, Error> {
+        self.query.prepare(conn.connection())?.query_scalar(self.params)
+    }
 }
 
-pub struct BoundQuery<Row, P> {
-    query: Query<Row>,
-    params: P,
+/// A query prepared against a particular connection, which can be executed
+/// repeatedly with different parameters without being prepared again.
+pub struct PreparedQuery<'conn, Row, Params> {
+    stmt: CachedStatement<'conn>,
+    _phantom: PhantomData<(Row, Params)>,
 }
 
-impl<Row: ColumnList, P: Params> BoundQuery<Row, P> {
-    pub fn query_all<T: FromRow<Row>>(self, conn: &mut Connection) -> Result<Vec<T>, Error> {
-        conn.prepare(self.query.sql)?
-            .query_map(self.params, |row| {
-                let reader = RowReader::<Row>::new(row);
-                let (row, _) = T::from_row(reader)?;
-                Ok(row)
-            })?
-            .collect::<Result<Vec<_>, _>>()
+impl<'conn, Row: ColumnList, Params: ParamList> PreparedQuery<'conn, Row, Params> {
+    fn rows<B: BindParams<Params>>(&mut self, params: B) -> Result<Rows<'_>, Error> {
+        bind_and_query(&mut self.stmt, params)
+    }
+
+    pub fn query_all<T: FromRow<Row>, B: BindParams<Params>>(&mut self, params: B) -> Result<Vec<T>, Error> {
+        let rows = self.rows(params)?
+            .mapped(read_row::<Row, T>)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn query_one<T: FromRow<Row>, B: BindParams<Params>>(&mut self, params: B) -> Result<T, Error> {
+        self.query_optional(params)?.ok_or(Error::NoRows)
+    }
+
+    pub fn query_optional<T: FromRow<Row>, B: BindParams<Params>>(&mut self, params: B) -> Result<Option<T>, Error> {
+        let mut rows = self.rows(params)?.mapped(read_row::<Row, T>);
+
+        let Some(row) = rows.next().transpose()? else {
+            return Ok(None);
+        };
+
+        if rows.next().is_some() {
+            return Err(Error::TooManyRows);
+        }
+
+        Ok(Some(row))
+    }
+
+    /// Returns an iterator reading rows lazily as it is advanced, rather than
+    /// collecting the full result set up front. The query can't be run again
+    /// until the iterator is dropped.
+    pub fn query_iter<T: FromRow<Row>, B: BindParams<Params>>(&mut self, params: B) -> Result<QueryIter<'_, Row, T>, Error> {
+        let rows = self.rows(params)?;
+        Ok(QueryIter { rows, _phantom: PhantomData })
+    }
+
+    pub fn query_for_each<T, B, F>(&mut self, params: B, mut f: F) -> Result<(), Error>
+        where T: FromRow<Row>, B: BindParams<Params>, F: FnMut(T)
+    {
+        self.query_fold(params, (), |(), row| f(row))
+    }
+
+    pub fn query_fold<T, B, Acc, F>(&mut self, params: B, init: Acc, mut f: F) -> Result<Acc, Error>
+        where T: FromRow<Row>, B: BindParams<Params>, F: FnMut(Acc, T) -> Acc
+    {
+        let mut rows = self.rows(params)?;
+        let mut acc = init;
+
+        while let Some(row) = rows.next()? {
+            acc = f(acc, read_row::<Row, T>(row)?);
+        }
+
+        Ok(acc)
+    }
+}
+
+impl<'conn, Col: Column, Params: ParamList> PreparedQuery<'conn, ColumnCons<Col, ()>, Params> {
+    pub fn query_scalar<B: BindParams<Params>>(&mut self, params: B) -> Result<Col::DomainType, Error> {
+        let (value,) = self.query_one(params)?;
+        Ok(value)
+    }
+}
+
+fn bind_and_query<'a, Params: ParamList, B: BindParams<Params>>(stmt: &'a mut CachedStatement, params: B) -> Result<Rows<'a>, Error> {
+    params.bind_params(stmt)?;
+    Ok(stmt.raw_query())
+}
+
+fn read_row<Row: ColumnList, T: FromRow<Row>>(row: &rusqlite::Row) -> Result<T, rusqlite::Error> {
+    let reader = RowReader::<Row>::new(row);
+    let (row, _) = T::from_row(reader)?;
+    Ok(row)
+}
+
+/// Iterator reading the rows of a query as it is advanced, returned by
+/// [`PreparedQuery::query_iter`]
+pub struct QueryIter<'stmt, Row, T> {
+    rows: Rows<'stmt>,
+    _phantom: PhantomData<fn() -> (Row, T)>,
+}
+
+impl<'stmt, Row: ColumnList, T: FromRow<Row>> Iterator for QueryIter<'stmt, Row, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rows.next() {
+            Ok(Some(row)) => Some(read_row::<Row, T>(row).map_err(Error::from)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Expr;
+    use crate::types::sql::Integer;
+
+    type Ints = ColumnCons<Expr<Integer>, ()>;
+
+    fn conn_with(values: &[i64]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (x INTEGER NOT NULL)").unwrap();
+
+        for value in values {
+            conn.execute("INSERT INTO t (x) VALUES (?1)", [value]).unwrap();
+        }
+
+        conn
+    }
+
+    const ALL: Query<Ints, ()> = Query::new_unchecked("SELECT x FROM t ORDER BY x");
+
+    #[test]
+    fn query_one_returns_the_single_row() {
+        let mut conn = conn_with(&[7]);
+        let (x,): (i64,) = ALL.bind(()).query_one(&mut conn).unwrap();
+        assert_eq!(x, 7);
+    }
+
+    #[test]
+    fn query_one_fails_without_exactly_one_row() {
+        let mut conn = conn_with(&[]);
+        assert!(matches!(ALL.bind(()).query_one::<(i64,)>(&mut conn), Err(Error::NoRows)));
+
+        let mut conn = conn_with(&[1, 2]);
+        assert!(matches!(ALL.bind(()).query_one::<(i64,)>(&mut conn), Err(Error::TooManyRows)));
+    }
+
+    #[test]
+    fn query_optional_returns_none_for_no_rows() {
+        let mut conn = conn_with(&[]);
+        assert_eq!(ALL.bind(()).query_optional::<(i64,)>(&mut conn).unwrap(), None);
+
+        let mut conn = conn_with(&[3]);
+        assert_eq!(ALL.bind(()).query_optional::<(i64,)>(&mut conn).unwrap(), Some((3,)));
+
+        let mut conn = conn_with(&[1, 2]);
+        assert!(matches!(ALL.bind(()).query_optional::<(i64,)>(&mut conn), Err(Error::TooManyRows)));
+    }
+
+    #[test]
+    fn query_iter_reads_all_rows() {
+        let conn = conn_with(&[1, 2, 3]);
+
+        let mut prepared = ALL.prepare(&conn).unwrap();
+        let rows = prepared.query_iter::<(i64,), _>(()).unwrap()
+            .collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(rows, [(1,), (2,), (3,)]);
+    }
+
+    #[test]
+    fn query_iter_dropped_early_resets_the_statement() {
+        let conn = conn_with(&[1, 2, 3]);
+        let mut prepared = ALL.prepare(&conn).unwrap();
+
+        let mut iter = prepared.query_iter::<(i64,), _>(()).unwrap();
+        assert_eq!(iter.next().unwrap().unwrap(), (1,));
+        drop(iter);
+
+        // the statement must start again from the first row
+        let rows = prepared.query_all::<(i64,), _>(()).unwrap();
+        assert_eq!(rows, [(1,), (2,), (3,)]);
+    }
+
+    #[test]
+    fn query_iter_reports_errors_stepping() {
+        let mut conn = conn_with(&[i64::MIN]);
+        let overflow = Query::<Ints, ()>::new_unchecked("SELECT abs(x) FROM t");
+
+        let mut prepared = overflow.prepare(&conn).unwrap();
+        let mut iter = prepared.query_iter::<(i64,), _>(()).unwrap();
+        assert!(matches!(iter.next(), Some(Err(Error::Sqlite(_)))));
+        drop(iter);
+        drop(prepared);
+
+        assert_eq!(ALL.bind(()).query_all::<(i64,)>(&mut conn).unwrap(), [(i64::MIN,)]);
+    }
+
+    #[test]
+    fn query_fold_and_for_each_visit_rows_in_order() {
+        let mut conn = conn_with(&[1, 2, 3]);
+
+        let sum = ALL.bind(()).query_fold(&mut conn, 0, |acc, (x,): (i64,)| acc * 10 + x).unwrap();
+        assert_eq!(sum, 123);
+
+        let mut seen = Vec::new();
+        ALL.bind(()).query_for_each(&mut conn, |(x,): (i64,)| seen.push(x)).unwrap();
+        assert_eq!(seen, [1, 2, 3]);
+    }
+
+    /// Number of statements prepared on the connection which haven't been
+    /// finalized, including those held in its cache
+    fn live_statements(conn: &Connection) -> usize {
+        let mut count = 0;
+
+        // SAFETY: the handle is only used to walk the connection's
+        // statements, which are neither used nor freed meanwhile
+        unsafe {
+            let db = conn.handle();
+            let mut stmt = rusqlite::ffi::sqlite3_next_stmt(db, std::ptr::null_mut());
+
+            while !stmt.is_null() {
+                count += 1;
+                stmt = rusqlite::ffi::sqlite3_next_stmt(db, stmt);
+            }
+        }
+
+        count
+    }
+
+    #[test]
+    fn warmed_statements_are_served_from_the_cache() {
+        let mut conn = conn_with(&[1, 2]);
+        let count = Query::<Ints, ()>::new_unchecked("SELECT count(*) FROM t");
+
+        warm_cache(&conn, [ALL.sql()]).unwrap();
+        count.warm(&conn).unwrap();
+        assert_eq!(live_statements(&conn), 2);
+
+        // running the queries reuses the cached statements
+        ALL.bind(()).query_all::<(i64,)>(&mut conn).unwrap();
+        count.bind(()).query_scalar(&mut conn).unwrap();
+        assert_eq!(live_statements(&conn), 2);
+
+        conn.flush_prepared_statement_cache();
+        assert_eq!(live_statements(&conn), 0);
+    }
+
+    #[test]
+    fn query_scalar_returns_the_single_value() {
+        let mut conn = conn_with(&[1, 2, 3]);
+        let count = Query::<Ints, ()>::new_unchecked("SELECT count(*) FROM t");
+        assert_eq!(count.bind(()).query_scalar(&mut conn).unwrap(), 3);
     }
 }
@@ -0,0 +1,128 @@
+use crate::{Connection, Error};
+use crate::executor::Executor;
+
+/// When a transaction takes its locks on the database, see
+/// <https://www.sqlite.org/lang_transaction.html>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionBehavior {
+    /// Locks are taken when the database is first read or written
+    Deferred,
+    /// A write lock is taken immediately
+    Immediate,
+    /// As Immediate, but also prevents readers outside of WAL mode
+    Exclusive,
+}
+
+/// A transaction, or a savepoint nested within one, which is rolled back
+/// when dropped unless committed
+pub struct Transaction<'conn> {
+    conn: &'conn Connection,
+    /// Number of savepoints this is nested within, 0 for the transaction
+    depth: usize,
+    finished: bool,
+}
+
+impl<'conn> Transaction<'conn> {
+    pub fn begin(conn: &'conn mut Connection, behavior: TransactionBehavior) -> Result<Self, Error> {
+        let sql = match behavior {
+            TransactionBehavior::Deferred => "BEGIN DEFERRED",
+            TransactionBehavior::Immediate => "BEGIN IMMEDIATE",
+            TransactionBehavior::Exclusive => "BEGIN EXCLUSIVE",
+        };
+
+        conn.execute_batch(sql)?;
+        Ok(Transaction { conn, depth: 0, finished: false })
+    }
+
+    /// Begins a savepoint within this transaction, which can be committed
+    /// or rolled back independently of it
+    pub fn savepoint(&mut self) -> Result<Transaction<'_>, Error> {
+        let depth = self.depth + 1;
+        self.conn.execute_batch(&format!("SAVEPOINT {}", savepoint_name(depth)))?;
+        Ok(Transaction { conn: self.conn, depth, finished: false })
+    }
+
+    /// Runs `f` in a savepoint, releasing it if `f` returns Ok and rolling
+    /// it back otherwise
+    pub fn with_savepoint<T, E, F>(&mut self, f: F) -> Result<T, E>
+        where E: From<Error>, F: FnOnce(&mut Transaction) -> Result<T, E>
+    {
+        let mut savepoint = self.savepoint()?;
+        let result = f(&mut savepoint);
+
+        match result {
+            Ok(_) => savepoint.commit()?,
+            Err(_) => savepoint.rollback()?,
+        }
+
+        result
+    }
+
+    /// Commits the transaction, or releases the savepoint. If this fails,
+    /// as when deferred constraints are violated, it's rolled back instead.
+    pub fn commit(mut self) -> Result<(), Error> {
+        let sql = match self.depth {
+            0 => "COMMIT".to_owned(),
+            depth => format!("RELEASE {}", savepoint_name(depth)),
+        };
+
+        // left unfinished on failure, so dropping self rolls back
+        self.conn.execute_batch(&sql)?;
+        self.finished = true;
+        Ok(())
+    }
+
+    pub fn rollback(mut self) -> Result<(), Error> {
+        self.rollback_inner()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    fn rollback_inner(&self) -> Result<(), Error> {
+        let sql = match self.depth {
+            0 => "ROLLBACK".to_owned(),
+            // rolling back to a savepoint leaves it open
+            depth => format!("ROLLBACK TO {name}; RELEASE {name}", name = savepoint_name(depth)),
+        };
+
+        self.conn.execute_batch(&sql)?;
+        Ok(())
+    }
+}
+
+impl Executor for Transaction<'_> {
+    fn connection(&self) -> &Connection {
+        self.conn
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        // some errors roll back the whole transaction themselves
+        let ended = self.depth == 0 && self.conn.is_autocommit();
+
+        if !self.finished && !ended {
+            let _ = self.rollback_inner();
+        }
+    }
+}
+
+/// Runs `f` in a transaction, committing it if `f` returns Ok and rolling
+/// it back otherwise
+pub fn transaction<T, E, F>(conn: &mut Connection, behavior: TransactionBehavior, f: F) -> Result<T, E>
+    where E: From<Error>, F: FnOnce(&mut Transaction) -> Result<T, E>
+{
+    let mut tx = Transaction::begin(conn, behavior)?;
+    let result = f(&mut tx);
+
+    match result {
+        Ok(_) => tx.commit()?,
+        Err(_) => tx.rollback()?,
+    }
+
+    result
+}
+
+fn savepoint_name(depth: usize) -> String {
+    format!("sqlitemapper_savepoint_{}", depth)
+}
@@ -20,3 +20,12 @@ pub trait Column: Sized {
     type SqlType: SqlType;
     type DomainType: FromSql<Self::SqlType>;
 }
+
+/// Column computed by an expression in a query rather than read from a
+/// table, with the SQL type inferred from the expression
+pub struct Expr<S: SqlType, D = <S as SqlType>::OwnedRustType>(PhantomData<(S, D)>);
+
+impl<S: SqlType, D: FromSql<S>> Column for Expr<S, D> {
+    type SqlType = S;
+    type DomainType = D;
+}
@@ -1,11 +1,12 @@
+use std::borrow::Cow;
 use std::fmt::Debug;
 use std::str::FromStr;
 
-use rusqlite::types::Type;
+use rusqlite::types::{Type, Value, ValueRef};
 use thiserror::Error;
 
 use crate::types::SqlType;
-use crate::types::sql::{Integer, Real, Text, Blob, Nullable};
+use crate::types::sql::{Integer, Real, Text, Blob, Boolean, Numeric, Any, Strict, Nullable};
 
 #[derive(Error, Debug)]
 #[error("sqlitemapper type conversion error: from {data_type} to {target_type}: {error:?}")]
@@ -59,7 +60,7 @@ impl<T> FromSql<Real> for T
 impl<T> FromSql<Text> for T
     where T: FromStr, T::Err: Debug + Send + Sync + 'static
 {
-    fn from_sql<'a>(value: &'a str) -> Result<Self, ConversionError> {
+    fn from_sql(value: &str) -> Result<Self, ConversionError> {
         value.parse().map_err(|err|
             ConversionError::new::<T, _>(Type::Text, err))
     }
@@ -70,12 +71,49 @@ impl<T> FromSql<Blob> for T
         for<'a> T: TryFrom<&'a [u8]>,
         for<'a> <T as TryFrom<&'a [u8]>>::Error: Debug + Send + Sync + 'static,
 {
-    fn from_sql<'a>(value: &'a [u8]) -> Result<Self, ConversionError> {
+    fn from_sql(value: &[u8]) -> Result<Self, ConversionError> {
         value.try_into().map_err(|err|
             ConversionError::new::<T, _>(Type::Blob, err))
     }
 }
 
+impl<T> FromSql<Boolean> for T
+    where T: From<bool>
+{
+    fn from_sql(value: bool) -> Result<Self, ConversionError> {
+        Ok(value.into())
+    }
+}
+
+/// Values of NUMERIC columns are converted from their text form, as numbers
+/// or dates may be
+impl<T> FromSql<Numeric> for T
+    where T: FromStr, T::Err: Debug + Send + Sync + 'static
+{
+    fn from_sql(value: Cow<str>) -> Result<Self, ConversionError> {
+        value.parse().map_err(|err|
+            ConversionError::new::<T, _>(Type::Text, err))
+    }
+}
+
+/// Values of ANY columns may be of any type, so are converted by rusqlite's
+/// own conversions, which accept whichever types make sense for the target
+impl<T> FromSql<Any> for T
+    where T: rusqlite::types::FromSql
+{
+    fn from_sql(value: ValueRef) -> Result<Self, ConversionError> {
+        T::column_result(value).map_err(|err|
+            ConversionError::new::<T, _>(value.data_type(), err))
+    }
+}
+
+impl<Inner: SqlType, T: FromSql<Inner>> FromSql<Strict<Inner>> for T
+{
+    fn from_sql<'a>(value: Inner::RustType<'a>) -> Result<Self, ConversionError> {
+        T::from_sql(value)
+    }
+}
+
 impl<Inner: SqlType, T: FromSql<Inner>> FromSql<Nullable<Inner>> for Option<T>
 {
     fn from_sql<'a>(value: Option<Inner::RustType<'a>>) -> Result<Option<T>, ConversionError> {
@@ -85,3 +123,110 @@ impl<Inner: SqlType, T: FromSql<Inner>> FromSql<Nullable<Inner>> for Option<T>
         }
     }
 }
+
+/// Conversion of a Rust value into a value of SQL type `SqlT` to be bound to
+/// a parameter, the counterpart of `FromSql`
+pub trait IntoSql<SqlT: SqlType> {
+    fn into_sql(self) -> SqlT::OwnedRustType;
+}
+
+impl<T: Into<i64>> IntoSql<Integer> for T {
+    fn into_sql(self) -> i64 {
+        self.into()
+    }
+}
+
+impl<T: Into<f64>> IntoSql<Real> for T {
+    fn into_sql(self) -> f64 {
+        self.into()
+    }
+}
+
+impl<T: ToString> IntoSql<Text> for T {
+    fn into_sql(self) -> String {
+        self.to_string()
+    }
+}
+
+impl<T: AsRef<[u8]>> IntoSql<Blob> for T {
+    fn into_sql(self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+}
+
+impl<T: Into<bool>> IntoSql<Boolean> for T {
+    fn into_sql(self) -> bool {
+        self.into()
+    }
+}
+
+/// Text which is a number is stored as one in NUMERIC columns
+impl<T: ToString> IntoSql<Numeric> for T {
+    fn into_sql(self) -> String {
+        self.to_string()
+    }
+}
+
+impl<T: Into<Value>> IntoSql<Any> for T {
+    fn into_sql(self) -> Value {
+        self.into()
+    }
+}
+
+impl<Inner: SqlType, T: IntoSql<Inner>> IntoSql<Strict<Inner>> for T {
+    fn into_sql(self) -> Inner::OwnedRustType {
+        self.into_sql()
+    }
+}
+
+impl<Inner: SqlType, T: IntoSql<Inner>> IntoSql<Nullable<Inner>> for Option<T> {
+    fn into_sql(self) -> Option<Inner::OwnedRustType> {
+        self.map(T::into_sql)
+    }
+}
+
+/// SQL type a Rust type is read from by default, used for annotated result
+/// columns whose SQL type can't be inferred from the query
+pub trait DefaultSqlType {
+    type SqlType: SqlType;
+}
+
+macro_rules! impl_default_sql_type {
+    ( $sql:ident: $( $ty:ty ),* ) => {
+        $(
+            impl DefaultSqlType for $ty {
+                type SqlType = $sql;
+            }
+        )*
+    };
+}
+
+impl_default_sql_type!(Integer: i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+impl_default_sql_type!(Real: f64);
+impl_default_sql_type!(Text: String);
+impl_default_sql_type!(Blob: Vec<u8>);
+impl_default_sql_type!(Boolean: bool);
+impl_default_sql_type!(Any: Value);
+
+impl<T: DefaultSqlType> DefaultSqlType for Option<T> {
+    type SqlType = Nullable<T::SqlType>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn into_sql_converts_by_sql_type() {
+        assert_eq!(<u8 as IntoSql<Integer>>::into_sql(7), 7i64);
+        assert_eq!(<f32 as IntoSql<Real>>::into_sql(0.5), 0.5f64);
+        assert_eq!(<u32 as IntoSql<Text>>::into_sql(42), "42");
+        assert_eq!(<&str as IntoSql<Blob>>::into_sql("ab"), b"ab");
+        assert!(<bool as IntoSql<Boolean>>::into_sql(true));
+        assert_eq!(<f64 as IntoSql<Numeric>>::into_sql(10.5), "10.5");
+        assert_eq!(<i64 as IntoSql<Any>>::into_sql(1), Value::Integer(1));
+        assert_eq!(<i32 as IntoSql<Strict<Integer>>>::into_sql(1), 1i64);
+        assert_eq!(<Option<&str> as IntoSql<Nullable<Text>>>::into_sql(None), None);
+        assert_eq!(<Option<&str> as IntoSql<Nullable<Text>>>::into_sql(Some("a")), Some("a".to_owned()));
+    }
+}
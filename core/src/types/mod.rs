@@ -1,8 +1,11 @@
 mod column;
-pub use column::{Column, ColumnCons, ColumnList};
+pub use column::{Column, ColumnCons, ColumnList, Expr};
 
 pub mod sql;
 pub use sql::SqlType;
 
+pub mod param;
+pub use param::{ParamCons, ParamList};
+
 mod convert;
-pub use convert::{FromSql, ConversionError};
+pub use convert::{FromSql, IntoSql, ConversionError, DefaultSqlType};
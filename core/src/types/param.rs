@@ -0,0 +1,126 @@
+use std::marker::PhantomData;
+
+use rusqlite::{Error, Statement, ToSql};
+
+use super::{Column, IntoSql, SqlType};
+use super::sql::Nullability;
+
+pub struct ParamCons<P, Tail: ParamList>(PhantomData<(P, Tail)>);
+
+pub trait ParamList {
+    const N: usize;
+}
+
+impl ParamList for () {
+    const N: usize = 0;
+}
+
+impl<P, Tail: ParamList> ParamList for ParamCons<P, Tail> {
+    const N: usize = 1 + Tail::N;
+}
+
+/// Parameter inserted into or assigned to a column, or compared with one by
+/// IS, taking any value convertible into the column's SQL type
+pub struct ColumnParam<C: Column>(PhantomData<C>);
+
+/// Parameter compared with a column by an operator which is never true for
+/// NULL, as in `col = ?`, taking any value convertible into the NOT NULL
+/// form of the column's SQL type
+pub struct ComparedParam<C: Column>(PhantomData<C>);
+
+/// Parameter with a known SQL type but no column, eg. the operand of LIMIT
+pub struct SqlParam<S: SqlType>(PhantomData<S>);
+
+/// Parameter used in a context its type could not be determined from,
+/// taking any value rusqlite can bind
+pub struct Untyped(PhantomData<()>);
+
+/// Implemented for the Rust types which may be bound to a parameter `P`
+pub trait BindParam<P> {
+    fn bind_param(self, stmt: &mut Statement, index: usize) -> Result<(), Error>;
+}
+
+impl<C: Column, T: IntoSql<C::SqlType>> BindParam<ColumnParam<C>> for T
+    where <C::SqlType as SqlType>::OwnedRustType: ToSql
+{
+    fn bind_param(self, stmt: &mut Statement, index: usize) -> Result<(), Error> {
+        stmt.raw_bind_parameter(index, self.into_sql())
+    }
+}
+
+impl<C: Column, T> BindParam<ComparedParam<C>> for T
+    where C::SqlType: Nullability,
+          T: IntoSql<<C::SqlType as Nullability>::NonNull>,
+          <<C::SqlType as Nullability>::NonNull as SqlType>::OwnedRustType: ToSql
+{
+    fn bind_param(self, stmt: &mut Statement, index: usize) -> Result<(), Error> {
+        stmt.raw_bind_parameter(index, self.into_sql())
+    }
+}
+
+impl<S: SqlType, T: IntoSql<S>> BindParam<SqlParam<S>> for T
+    where S::OwnedRustType: ToSql
+{
+    fn bind_param(self, stmt: &mut Statement, index: usize) -> Result<(), Error> {
+        stmt.raw_bind_parameter(index, self.into_sql())
+    }
+}
+
+impl<T: ToSql> BindParam<Untyped> for T {
+    fn bind_param(self, stmt: &mut Statement, index: usize) -> Result<(), Error> {
+        stmt.raw_bind_parameter(index, self)
+    }
+}
+
+/// Implemented for tuples of values which may be bound to each parameter in
+/// the list `List`, in order
+pub trait BindParams<List: ParamList> {
+    fn bind_params(self, stmt: &mut Statement) -> Result<(), Error>;
+}
+
+impl BindParams<()> for () {
+    fn bind_params(self, _: &mut Statement) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+macro_rules! __make_param_cons {
+    ( ( $param:ident, $($rest:ident,)* ) ) => {
+        ParamCons< $param, __make_param_cons!{ ( $($rest,)* ) } >
+    };
+    ( () ) => { () };
+}
+
+macro_rules! impl_bind_params_for_tuple {
+    { ( $( $idx:tt: $param:ident $val:ident, )* ) } => {
+        impl < $( $param, $val: BindParam<$param>, )* >
+            BindParams< __make_param_cons! { ( $( $param, )* ) } >
+            for ( $( $val, )* )
+        {
+            fn bind_params(self, stmt: &mut Statement) -> Result<(), Error> {
+                $(
+                    self.$idx.bind_param(stmt, $idx + 1)?;
+                )*
+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_bind_params_for_tuple!{ (0: P1 V1,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8, 8: P9 V9,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8, 8: P9 V9, 9: P10 V10,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8, 8: P9 V9, 9: P10 V10, 10: P11 V11,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8, 8: P9 V9, 9: P10 V10, 10: P11 V11, 11: P12 V12,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8, 8: P9 V9, 9: P10 V10, 10: P11 V11, 11: P12 V12, 12: P13 V13,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8, 8: P9 V9, 9: P10 V10, 10: P11 V11, 11: P12 V12, 12: P13 V13, 13: P14 V14,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8, 8: P9 V9, 9: P10 V10, 10: P11 V11, 11: P12 V12, 12: P13 V13, 13: P14 V14, 14: P15 V15,) }
+impl_bind_params_for_tuple!{ (0: P1 V1, 1: P2 V2, 2: P3 V3, 3: P4 V4, 4: P5 V5, 5: P6 V6, 6: P7 V7, 7: P8 V8, 8: P9 V9, 9: P10 V10, 10: P11 V11, 11: P12 V12, 12: P13 V13, 13: P14 V14, 14: P15 V15, 15: P16 V16,) }
@@ -1,14 +1,24 @@
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
-use rusqlite::types::{ValueRef, FromSqlResult, Type};
+use rusqlite::types::{FromSqlError, FromSqlResult, Type, Value, ValueRef};
 
 pub trait SqlType: Sized {
     type RustType<'a>;
     type OwnedRustType;
 
     fn get<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>>;
+
+    /// Gets a value from a STRICT table, where SQLite guarantees the storage
+    /// class of values, so no fallback conversions are made
+    fn get_strict<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        Self::get(value)
+    }
 }
 
+/// Type of columns with INTEGER affinity. Outside of STRICT tables, reals
+/// which are whole numbers in the range of `i64` are read as integers too,
+/// as expressions such as `max(x, 0.0)` may give them.
 pub struct Integer(PhantomData<()>);
 
 impl SqlType for Integer {
@@ -16,6 +26,15 @@ impl SqlType for Integer {
     type OwnedRustType = i64;
 
     fn get<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        const RANGE: std::ops::Range<f64> = -9223372036854775808.0..9223372036854775808.0;
+
+        match value {
+            ValueRef::Real(f) if RANGE.contains(&f) && f.fract() == 0.0 => Ok(f as i64),
+            value => value.as_i64(),
+        }
+    }
+
+    fn get_strict<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
         value.as_i64()
     }
 }
@@ -27,6 +46,13 @@ impl SqlType for Real {
     type OwnedRustType = f64;
 
     fn get<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        match value {
+            ValueRef::Integer(i) => Ok(i as f64),
+            value => value.as_f64(),
+        }
+    }
+
+    fn get_strict<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
         value.as_f64()
     }
 }
@@ -49,10 +75,83 @@ impl SqlType for Blob {
     type OwnedRustType = Vec<u8>;
 
     fn get<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        // columns without a declared type have BLOB affinity, but store
+        // text as given
+        value.as_bytes()
+    }
+
+    fn get_strict<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
         value.as_blob()
     }
 }
 
+/// Type of columns holding booleans as the integers 0 and 1, as declared
+/// `BOOLEAN` or constrained by `CHECK (x IN (0, 1))`
+pub struct Boolean(PhantomData<()>);
+
+impl SqlType for Boolean {
+    type RustType<'a> = bool;
+    type OwnedRustType = bool;
+
+    fn get<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        match value.as_i64()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(FromSqlError::OutOfRange(other)),
+        }
+    }
+}
+
+/// Type of columns with NUMERIC affinity, as declared `NUMERIC`, `DECIMAL`
+/// or `DATETIME`, though not `BOOLEAN`, which is [`Boolean`]. Values are
+/// stored as integers or reals where they convert losslessly, but otherwise
+/// as given, as dates are. They're read and written in their text form, so
+/// convert to and from the same types as those of TEXT columns.
+pub struct Numeric(PhantomData<()>);
+
+impl SqlType for Numeric {
+    type RustType<'a> = Cow<'a, str>;
+    type OwnedRustType = String;
+
+    fn get<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        match value {
+            ValueRef::Integer(i) => Ok(Cow::Owned(i.to_string())),
+            ValueRef::Real(f) => Ok(Cow::Owned(f.to_string())),
+            ValueRef::Text(_) => value.as_str().map(Cow::Borrowed),
+            ValueRef::Null | ValueRef::Blob(_) => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// Type of ANY columns in STRICT tables, which hold values of any storage
+/// class exactly as given
+pub struct Any(PhantomData<()>);
+
+impl SqlType for Any {
+    type RustType<'a> = ValueRef<'a>;
+    type OwnedRustType = Value;
+
+    fn get<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        match value {
+            ValueRef::Null => Err(FromSqlError::InvalidType),
+            value => Ok(value),
+        }
+    }
+}
+
+/// Type of columns in STRICT tables, read without the fallback conversions
+/// of the inner type
+pub struct Strict<T: SqlType>(PhantomData<T>);
+
+impl<Inner: SqlType> SqlType for Strict<Inner> {
+    type RustType<'a> = Inner::RustType<'a>;
+    type OwnedRustType = Inner::OwnedRustType;
+
+    fn get<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        Inner::get_strict(value)
+    }
+}
+
 pub struct Nullable<T: SqlType>(PhantomData<T>);
 
 impl<Inner: SqlType> SqlType for Nullable<Inner> {
@@ -66,4 +165,92 @@ impl<Inner: SqlType> SqlType for Nullable<Inner> {
             Ok(Some(Inner::get(value)?))
         }
     }
+
+    fn get_strict<'a>(value: ValueRef<'a>) -> FromSqlResult<Self::RustType<'a>> {
+        if value.data_type() == Type::Null {
+            Ok(None)
+        } else {
+            Ok(Some(Inner::get_strict(value)?))
+        }
+    }
+}
+
+/// Maps a SQL type to its NOT NULL and nullable forms
+pub trait Nullability {
+    type NonNull: SqlType;
+    type Nullable: SqlType;
+}
+
+macro_rules! impl_nullability {
+    ( $( $ty:ident ),* ) => {
+        $(
+            impl Nullability for $ty {
+                type NonNull = $ty;
+                type Nullable = Nullable<$ty>;
+            }
+        )*
+    };
+}
+
+impl_nullability!(Integer, Real, Text, Blob, Boolean, Numeric, Any);
+
+impl<Inner: SqlType> Nullability for Strict<Inner> {
+    type NonNull = Strict<Inner>;
+    type Nullable = Nullable<Strict<Inner>>;
+}
+
+impl<Inner: SqlType> Nullability for Nullable<Inner> {
+    type NonNull = Inner;
+    type Nullable = Nullable<Inner>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_types_make_no_fallback_conversions() {
+        assert_eq!(Integer::get(ValueRef::Real(2.0)).unwrap(), 2);
+        assert!(Strict::<Integer>::get(ValueRef::Real(2.0)).is_err());
+
+        assert_eq!(Real::get(ValueRef::Integer(2)).unwrap(), 2.0);
+        assert!(Strict::<Real>::get(ValueRef::Integer(2)).is_err());
+
+        assert_eq!(Blob::get(ValueRef::Text(b"ab")).unwrap(), b"ab");
+        assert!(Strict::<Blob>::get(ValueRef::Text(b"ab")).is_err());
+
+        assert_eq!(Nullable::<Strict<Integer>>::get(ValueRef::Null).unwrap(), None);
+        assert!(Nullable::<Strict<Integer>>::get(ValueRef::Real(2.0)).is_err());
+    }
+
+    #[test]
+    fn integers_are_read_from_integral_reals_in_range() {
+        assert_eq!(Integer::get(ValueRef::Real(-9223372036854775808.0)).unwrap(), i64::MIN);
+        // 2^63, which would saturate to i64::MAX
+        assert!(Integer::get(ValueRef::Real(9223372036854775808.0)).is_err());
+        assert!(Integer::get(ValueRef::Real(2.5)).is_err());
+    }
+
+    #[test]
+    fn numeric_values_are_read_as_text() {
+        assert_eq!(Numeric::get(ValueRef::Integer(10)).unwrap(), "10");
+        assert_eq!(Numeric::get(ValueRef::Real(10.5)).unwrap(), "10.5");
+        assert_eq!(Numeric::get(ValueRef::Text(b"2024-01-01 00:00:00")).unwrap(), "2024-01-01 00:00:00");
+        assert!(Numeric::get(ValueRef::Blob(b"1")).is_err());
+    }
+
+    #[test]
+    fn booleans_are_zero_or_one() {
+        assert!(!Boolean::get(ValueRef::Integer(0)).unwrap());
+        assert!(Boolean::get(ValueRef::Integer(1)).unwrap());
+        assert!(matches!(Boolean::get(ValueRef::Integer(2)), Err(FromSqlError::OutOfRange(2))));
+        assert!(Boolean::get(ValueRef::Text(b"1")).is_err());
+    }
+
+    #[test]
+    fn any_values_are_given_as_stored() {
+        assert_eq!(Any::get(ValueRef::Text(b"1")).unwrap(), ValueRef::Text(b"1"));
+        assert_eq!(Any::get(ValueRef::Integer(1)).unwrap(), ValueRef::Integer(1));
+        assert!(Any::get(ValueRef::Null).is_err());
+    }
 }
@@ -0,0 +1,412 @@
+use std::fmt::{self, Display};
+
+use rusqlite::OptionalExtension;
+
+use crate::{Connection, Error};
+
+/// A table or view as seen by `schema!` at compile time
+#[derive(Debug, Clone, Copy)]
+pub struct TableInfo {
+    pub database: &'static str,
+    pub name: &'static str,
+    /// Whether this is a view. SQLite reports neither the types inferred for
+    /// the columns of views nor their nullability, so their columns are only
+    /// checked to exist, and are given without either.
+    pub view: bool,
+    pub columns: &'static [ColumnInfo],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnInfo {
+    pub name: &'static str,
+    pub type_: &'static str,
+    pub not_null: bool,
+}
+
+/// Hashes the names, types and nullability of the tables and columns, so
+/// that schemas can be told apart cheaply, as by a program recording which
+/// schema it last verified a database against
+pub const fn fingerprint(tables: &[TableInfo]) -> u64 {
+    let mut hasher = Hasher::new();
+    let mut t = 0;
+
+    while t < tables.len() {
+        let table = &tables[t];
+        hasher = hasher.table(table.database, table.name, table.view);
+
+        let mut c = 0;
+
+        while c < table.columns.len() {
+            let column = &table.columns[c];
+            hasher = hasher.column(column.name, column.type_, column.not_null);
+            c += 1;
+        }
+
+        t += 1;
+    }
+
+    hasher.0
+}
+
+/// FNV-1a, with a separator after each field so fields can't run together
+struct Hasher(u64);
+
+impl Hasher {
+    const fn new() -> Self {
+        Hasher(0xcbf29ce484222325)
+    }
+
+    const fn write(self, bytes: &[u8]) -> Self {
+        let mut state = self.0;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            state ^= bytes[i] as u64;
+            state = state.wrapping_mul(0x100000001b3);
+            i += 1;
+        }
+
+        state ^= 0xff;
+        Hasher(state.wrapping_mul(0x100000001b3))
+    }
+
+    const fn table(self, database: &str, name: &str, view: bool) -> Self {
+        self.write(database.as_bytes())
+            .write(name.as_bytes())
+            .write(if view { b"VIEW" } else { b"TABLE" })
+    }
+
+    const fn column(self, name: &str, type_: &str, not_null: bool) -> Self {
+        self.write(name.as_bytes())
+            .write(type_.as_bytes())
+            .write(if not_null { b"NOT NULL" } else { b"NULL" })
+    }
+}
+
+/// Difference between the schema a program was compiled against and the
+/// schema of a database it's connected to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaDifference {
+    MissingTable {
+        database: String,
+        table: String,
+    },
+    MissingView {
+        database: String,
+        view: String,
+    },
+    MissingColumn {
+        database: String,
+        table: String,
+        column: String,
+    },
+    TypeMismatch {
+        database: String,
+        table: String,
+        column: String,
+        expected: String,
+        actual: String,
+    },
+    NullabilityMismatch {
+        database: String,
+        table: String,
+        column: String,
+        expected_not_null: bool,
+    },
+}
+
+impl Display for SchemaDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaDifference::MissingTable { database, table } =>
+                write!(f, "table {}.{} is missing", database, table),
+            SchemaDifference::MissingView { database, view } =>
+                write!(f, "view {}.{} is missing", database, view),
+            SchemaDifference::MissingColumn { database, table, column } =>
+                write!(f, "column {}.{}.{} is missing", database, table, column),
+            SchemaDifference::TypeMismatch { database, table, column, expected, actual } =>
+                write!(f, "column {}.{}.{} has type {:?}, expected {:?}", database, table, column, actual, expected),
+            SchemaDifference::NullabilityMismatch { database, table, column, expected_not_null } => {
+                let expected = if *expected_not_null { "NOT NULL" } else { "nullable" };
+                write!(f, "column {}.{}.{} is not {}", database, table, column, expected)
+            }
+        }
+    }
+}
+
+/// Differences found by [`verify`], empty if the database matches
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    pub differences: Vec<SchemaDifference>,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+impl Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, difference) in self.differences.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}", difference)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares the tables and views of the database behind `conn` with
+/// `tables`. Extra tables and columns in the database are not differences,
+/// as queries compiled against `tables` are unaffected by them.
+pub fn verify(conn: &Connection, tables: &[TableInfo]) -> Result<SchemaDiff, Error> {
+    let mut diff = SchemaDiff::default();
+
+    for table in tables {
+        let columns = live_columns(conn, table, rusqlite::version_number())?;
+
+        if columns.is_empty() {
+            diff.differences.push(match table.view {
+                false => SchemaDifference::MissingTable {
+                    database: table.database.to_owned(),
+                    table: table.name.to_owned(),
+                },
+                true => SchemaDifference::MissingView {
+                    database: table.database.to_owned(),
+                    view: table.name.to_owned(),
+                },
+            });
+            continue;
+        }
+
+        for expected in table.columns {
+            let Some((_, type_, not_null)) = columns.iter().find(|(name, ..)| name == expected.name) else {
+                diff.differences.push(SchemaDifference::MissingColumn {
+                    database: table.database.to_owned(),
+                    table: table.name.to_owned(),
+                    column: expected.name.to_owned(),
+                });
+                continue;
+            };
+
+            if table.view {
+                continue;
+            }
+
+            if !type_.eq_ignore_ascii_case(expected.type_) {
+                diff.differences.push(SchemaDifference::TypeMismatch {
+                    database: table.database.to_owned(),
+                    table: table.name.to_owned(),
+                    column: expected.name.to_owned(),
+                    expected: expected.type_.to_owned(),
+                    actual: type_.clone(),
+                });
+            }
+
+            if *not_null != expected.not_null {
+                diff.differences.push(SchemaDifference::NullabilityMismatch {
+                    database: table.database.to_owned(),
+                    table: table.name.to_owned(),
+                    column: expected.name.to_owned(),
+                    expected_not_null: expected.not_null,
+                });
+            }
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Name, type and NOT NULL of each column of the table or view in the
+/// database, or none if it doesn't exist
+fn live_columns(conn: &Connection, table: &TableInfo, sqlite_version: i32) -> Result<Vec<(String, String, bool)>, Error> {
+    // table_info lists the columns of tables and views alike, so a table
+    // replaced by a view of the same name would otherwise go unnoticed
+    if !exists(conn, table, sqlite_version)? {
+        return Ok(Vec::new());
+    }
+
+    let columns = conn
+        .prepare("SELECT name, type, \"notnull\" FROM pragma_table_info(?1, ?2)")?
+        .query_map([table.name, table.database], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(columns)
+}
+
+/// Whether the database has a table or view, as `table` is, of its name.
+/// This is reported by table_list since SQLite 3.37, and before that is
+/// looked up in the schema table of the database, if it's attached.
+fn exists(conn: &Connection, table: &TableInfo, sqlite_version: i32) -> Result<bool, Error> {
+    let kind = match table.view {
+        false => "table",
+        true => "view",
+    };
+
+    if sqlite_version >= 3_037_000 {
+        let found = conn
+            .query_row(
+                "SELECT 1 FROM pragma_table_list(?2) WHERE schema = ?1 AND type = ?3",
+                [table.database, table.name, kind],
+                |_| Ok(()),
+            )
+            .optional()?;
+
+        return Ok(found.is_some());
+    }
+
+    let attached = conn
+        .query_row(
+            "SELECT 1 FROM pragma_database_list WHERE name = ?1 COLLATE NOCASE",
+            [table.database],
+            |_| Ok(()),
+        )
+        .optional()?;
+
+    if attached.is_none() {
+        return Ok(false);
+    }
+
+    let sql = format!(
+        "SELECT 1 FROM \"{}\".sqlite_master WHERE name = ?1 COLLATE NOCASE AND type = ?2",
+        table.database.replace('"', "\"\""),
+    );
+
+    let found = conn.query_row(&sql, [table.name, kind], |_| Ok(())).optional()?;
+
+    Ok(found.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USERS: TableInfo = TableInfo {
+        database: "main",
+        name: "users",
+        view: false,
+        columns: &[
+            ColumnInfo { name: "id", type_: "INTEGER", not_null: true },
+            ColumnInfo { name: "name", type_: "TEXT", not_null: false },
+        ],
+    };
+
+    const NAMES: TableInfo = TableInfo {
+        database: "main",
+        name: "names",
+        view: true,
+        columns: &[ColumnInfo { name: "name", type_: "", not_null: false }],
+    };
+
+    const TABLES: &[TableInfo] = &[USERS, NAMES];
+
+    fn open(sql: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(sql).unwrap();
+        conn
+    }
+
+    #[test]
+    fn fingerprints_differ_by_schema() {
+        const RENAMED: &[TableInfo] = &[TableInfo { name: "people", ..USERS }, NAMES];
+        const AS_VIEW: &[TableInfo] = &[TableInfo { view: true, ..USERS }, NAMES];
+
+        assert_eq!(fingerprint(TABLES), fingerprint(TABLES));
+        assert_ne!(fingerprint(TABLES), fingerprint(RENAMED));
+        assert_ne!(fingerprint(TABLES), fingerprint(AS_VIEW));
+        assert_ne!(fingerprint(TABLES), fingerprint(&[USERS]));
+    }
+
+    #[test]
+    fn matching_databases_have_no_differences() {
+        let conn = open("
+            CREATE TABLE users (id INTEGER NOT NULL, name TEXT, extra BLOB);
+            CREATE VIEW names AS SELECT upper(name) AS name FROM users;
+        ");
+
+        assert!(verify(&conn, TABLES).unwrap().is_empty());
+    }
+
+    #[test]
+    fn finds_tables_without_table_list() {
+        const ARCHIVED: TableInfo = TableInfo { database: "archive", ..USERS };
+        const TEMP: TableInfo = TableInfo { database: "temp", ..NAMES };
+
+        let conn = open("
+            CREATE TABLE Users (id INTEGER NOT NULL, name TEXT);
+            CREATE TEMP VIEW names AS SELECT name FROM users;
+        ");
+
+        for table in [USERS, NAMES, ARCHIVED, TEMP, TableInfo { view: true, ..USERS }] {
+            assert_eq!(
+                exists(&conn, &table, 3_036_000).unwrap(),
+                exists(&conn, &table, rusqlite::version_number()).unwrap(),
+                "{}.{}", table.database, table.name,
+            );
+        }
+
+        assert!(exists(&conn, &USERS, 3_036_000).unwrap());
+        assert!(exists(&conn, &TEMP, 3_036_000).unwrap());
+        assert!(!exists(&conn, &ARCHIVED, 3_036_000).unwrap());
+    }
+
+    #[test]
+    fn reports_differences() {
+        let conn = open("
+            CREATE TABLE users (id integer, other TEXT);
+            CREATE TABLE names (name TEXT);
+        ");
+
+        let diff = verify(&conn, TABLES).unwrap();
+
+        assert_eq!(diff.differences, [
+            SchemaDifference::NullabilityMismatch {
+                database: "main".to_owned(),
+                table: "users".to_owned(),
+                column: "id".to_owned(),
+                expected_not_null: true,
+            },
+            SchemaDifference::MissingColumn {
+                database: "main".to_owned(),
+                table: "users".to_owned(),
+                column: "name".to_owned(),
+            },
+            SchemaDifference::MissingView {
+                database: "main".to_owned(),
+                view: "names".to_owned(),
+            },
+        ]);
+
+        assert_eq!(diff.to_string(), "column main.users.id is not NOT NULL\ncolumn main.users.name is missing\nview main.names is missing");
+    }
+
+    #[test]
+    fn reports_type_mismatches_and_missing_databases() {
+        const ARCHIVED: TableInfo = TableInfo { database: "archive", ..USERS };
+
+        let conn = open("CREATE TABLE users (id TEXT NOT NULL, name TEXT)");
+        let tables = [USERS, ARCHIVED];
+
+        let diff = verify(&conn, &tables).unwrap();
+
+        assert_eq!(diff.differences, [
+            SchemaDifference::TypeMismatch {
+                database: "main".to_owned(),
+                table: "users".to_owned(),
+                column: "id".to_owned(),
+                expected: "INTEGER".to_owned(),
+                actual: "TEXT".to_owned(),
+            },
+            SchemaDifference::MissingTable {
+                database: "archive".to_owned(),
+                table: "users".to_owned(),
+            },
+        ]);
+    }
+}
@@ -1,7 +1,8 @@
+use std::fmt::{self, Display};
 use std::str::FromStr;
 
 use chrono::{DateTime, Utc, NaiveDateTime};
-use sqlitemapper::query;
+use sqlitemapper::{query, query_as, FromRow};
 use rusqlite::Connection;
 
 #[derive(Debug)]
@@ -17,9 +18,9 @@ impl FromStr for Timestamp {
     }
 }
 
-impl ToString for Timestamp {
-    fn to_string(&self) -> String {
-        self.0.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string()
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.naive_utc().format("%Y-%m-%d %H:%M:%S"))
     }
 }
 
@@ -31,16 +32,71 @@ sqlitemapper::schema!{
     }
 }
 
-fn main() -> Result<(), rusqlite::Error> {
+#[derive(Debug, FromRow)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub created_at: Timestamp,
+}
+
+fn main() -> Result<(), sqlitemapper::Error> {
     let mut conn = Connection::open("database.db")?;
 
-    let users = query!(schema, "SELECT * FROM users")
-        .bind([])
-        .query_all::<(_, _, _, _)>(&mut conn)?;
+    let diff = schema::verify(&conn)?;
+    if !diff.is_empty() {
+        eprintln!("database.db does not match schema.sql:\n{}", diff);
+        return Ok(());
+    }
+
+    let username = "alice".to_owned();
+
+    let existing = query!(schema, "SELECT id, created_at FROM users WHERE username = :username", username = &username)
+        .query_optional::<(i64, Timestamp)>(&mut conn)?;
+
+    match existing {
+        Some((id, created_at)) => println!("found user {} created at {:?}", id, created_at),
+        None => {
+            let (id, created_at) = query!(schema, "INSERT INTO users (username, created_at) VALUES (:username, datetime('now')) RETURNING id, created_at", username)
+                .query_one::<(i64, Timestamp)>(&mut conn)?;
+
+            println!("created user {} at {:?}", id, created_at);
+        }
+    }
+
+    let id = schema::users::insert(schema::users::NewRecord {
+        username: "bob".to_owned(),
+        display_name: Some("Bob".to_owned()),
+        created_at: Timestamp(Utc::now()),
+    }).execute(&mut conn)?;
+
+    println!("inserted user {}", id);
+
+    let record = query!(schema, "SELECT * FROM users WHERE id = ?")
+        .bind((id,))
+        .query_one::<schema::users::Record>(&mut conn)?;
+
+    println!("user {} is named {}", record.id, record.username);
+
+    let users = query_as!(User, schema, "SELECT * FROM users")
+        .bind(())
+        .query_all::<User>(&mut conn)?;
 
     for user in users {
         println!("{:?}", user);
     }
 
+    let last_id = query!(schema, "SELECT id FROM users ORDER BY id DESC LIMIT 1")
+        .bind(())
+        .query_optional::<(_,)>(&mut conn)?;
+
+    println!("last user id: {:?}", last_id);
+
+    let count = query!(schema, "SELECT count(*) FROM users")
+        .bind(())
+        .query_scalar(&mut conn)?;
+
+    println!("{} users", count);
+
     Ok(())
 }
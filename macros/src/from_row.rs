@@ -0,0 +1,67 @@
+use proc_macro2::{TokenStream as TokenStream2, Ident, Span};
+use proc_macro_error::abort;
+use quote::{quote, format_ident};
+use syn::{Data, DeriveInput, Fields, GenericParam, parse_quote};
+use syn::ext::IdentExt;
+
+pub fn derive_from_row_impl(input: DeriveInput) -> TokenStream2 {
+    let ident = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        abort!(ident.span(), "FromRow can only be derived for structs");
+    };
+
+    let Fields::Named(fields) = &data.fields else {
+        abort!(ident.span(), "FromRow can only be derived for structs with named fields");
+    };
+
+    let field_idents = fields.named.iter()
+        .map(|field| field.ident.clone().unwrap())
+        .collect::<Vec<_>>();
+
+    let field_names = field_idents.iter()
+        .map(|ident| ident.unraw().to_string())
+        .collect::<Vec<_>>();
+
+    let column_params = (1..=field_idents.len())
+        .map(|i| format_ident!("C{}", i, span = Span::mixed_site()))
+        .collect::<Vec<_>>();
+
+    let row_type = column_params.iter()
+        .rev()
+        .fold(quote! { () }, |tail, col| {
+            quote! { ::sqlitemapper::types::ColumnCons<#col, #tail> }
+        });
+
+    let mut generics = input.generics.clone();
+
+    for (col, field) in column_params.iter().zip(&fields.named) {
+        let ty = &field.ty;
+        generics.params.push(GenericParam::Type(parse_quote! {
+            #col: ::sqlitemapper::types::Column<DomainType = #ty>
+        }));
+    }
+
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (struct_impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let reader = Ident::new("reader", Span::mixed_site());
+
+    quote! {
+        impl #impl_generics ::sqlitemapper::from_row::FromRow<#row_type> for #ident #ty_generics #where_clause {
+            fn from_row<'a>(
+                #reader: ::sqlitemapper::from_row::RowReader<'a, #row_type>,
+            ) -> ::core::result::Result<(Self, ::sqlitemapper::from_row::RowReader<'a, ()>), ::sqlitemapper::rusqlite::Error> {
+                #(
+                    let (#field_idents, #reader) = #reader.next()?;
+                )*
+
+                Ok((#ident { #( #field_idents, )* }, #reader))
+            }
+        }
+
+        impl #struct_impl_generics ::sqlitemapper::from_row::FromRowFields for #ident #ty_generics #where_clause {
+            const FIELDS: &'static [&'static str] = &[ #( #field_names, )* ];
+        }
+    }
+}
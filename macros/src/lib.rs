@@ -1,3 +1,4 @@
+mod from_row;
 mod query;
 mod schema;
 mod util;
@@ -5,7 +6,7 @@ mod util;
 use proc_macro::{self, TokenStream};
 use proc_macro_error::proc_macro_error;
 use quote::ToTokens;
-use syn::parse_macro_input;
+use syn::{parse_macro_input, DeriveInput};
 
 #[proc_macro_error]
 #[proc_macro]
@@ -19,3 +20,16 @@ pub fn schema(input: TokenStream) -> TokenStream {
 pub fn query(input: TokenStream) -> TokenStream {
     query::query_impl(input)
 }
+
+#[proc_macro_error]
+#[proc_macro]
+pub fn query_as(input: TokenStream) -> TokenStream {
+    query::query_as_impl(input)
+}
+
+#[proc_macro_error]
+#[proc_macro_derive(FromRow)]
+pub fn derive_from_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_row::derive_from_row_impl(input).into()
+}
@@ -1,62 +1,545 @@
 use derive_syn_parse::Parse;
 use proc_macro::{self, TokenStream};
 use proc_macro2::{TokenStream as TokenStream2, Ident, Span};
-use proc_macro_error::abort_call_site;
+use proc_macro_error::{abort, abort_call_site, emit_error};
 use quote::quote;
-use syn::{parse_macro_input, LitStr};
+use syn::parse::{ParseStream, Parse};
+use syn::punctuated::Punctuated;
+use syn::token::{Comma, DotDot, Eq, Underscore};
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, LitStr, Expr};
 
-use sqlitemapper_schema::{QueryInfo, ResultColumn};
+use sqlitemapper_schema::{Annotation, ColumnOrigin, ExprType, Nullability, ParamType, QueryInfo, QueryParam, ResultColumn, ValueType};
 
 #[derive(Parse)]
 struct QueryInput {
     schema: syn::Path,
-    _comma: syn::token::Comma,
+    _comma: Comma,
     query: LitStr,
+    #[call(parse_named_args)]
+    args: Vec<NamedArg>,
+}
+
+/// Value for a named parameter, as in format!: `name = expr`, or `name` to
+/// use the variable of the same name. `..expr` takes values for any
+/// remaining parameters from the same named fields of `expr`. Parameters
+/// given no value are captured from variables of the same name in scope,
+/// unless `_` is given alone to leave the query unbound.
+enum NamedArg {
+    Value { ident: Ident, value: Option<(Eq, Expr)> },
+    Rest { dots: DotDot, expr: Expr },
+    Unbound(Underscore),
+}
+
+impl Parse for NamedArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Underscore) {
+            return Ok(NamedArg::Unbound(input.parse()?));
+        }
+
+        if input.peek(DotDot) {
+            return Ok(NamedArg::Rest { dots: input.parse()?, expr: input.parse()? });
+        }
+
+        let ident = input.parse()?;
+
+        let value = if input.peek(Eq) {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
+
+        Ok(NamedArg::Value { ident, value })
+    }
+}
+
+fn parse_named_args(input: ParseStream) -> syn::Result<Vec<NamedArg>> {
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    input.parse::<Comma>()?;
+
+    let args = Punctuated::<NamedArg, Comma>::parse_terminated(input)?;
+    Ok(args.into_iter().collect())
+}
+
+#[derive(Parse)]
+struct QueryAsInput {
+    ty: syn::Path,
+    _comma: Comma,
+    query: QueryInput,
 }
 
 pub fn query_impl(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as QueryInput);
+    let info = prepare_query(&input.query.value());
+
+    query_expr(input, &info).into()
+}
+
+/// Like query!, but checks that the fields of a struct deriving FromRow
+/// match the result columns of the query by name, type and order
+pub fn query_as_impl(input: TokenStream) -> TokenStream {
+    let QueryAsInput { ty, query: input, .. } = parse_macro_input!(input as QueryAsInput);
+    let info = prepare_query(&input.query.value());
+
+    if is_execute(&info) {
+        abort!(input.query.span(), "query returns no rows");
+    }
+
+    let span = input.query.span();
+
+    let fields = info.columns()
+        .iter()
+        .map(|col| {
+            let name = col.name().unwrap_or_else(|| {
+                abort!(span, "{} has no name, alias it with AS", col.describe());
+            });
+
+            syn::parse_str::<Ident>(name)
+                .or_else(|_| syn::parse_str::<Ident>(&format!("r#{name}")))
+                .map(|ident| Ident::new_raw(&ident.unraw().to_string(), span))
+                .unwrap_or_else(|_| {
+                    abort!(span, "{} is not a valid Rust identifier, alias it with AS", col.describe());
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let names = fields.iter()
+        .map(|ident| ident.unraw().to_string())
+        .collect::<Vec<_>>();
+
+    let domain_types = info.columns()
+        .iter()
+        .map(|col| {
+            let column = column_type(&input.schema, col);
+            quote! { <#column as ::sqlitemapper::types::Column>::DomainType }
+        })
+        .collect::<Vec<_>>();
+
+    let indexes = (0..fields.len()).map(syn::Index::from);
+
+    let row = Ident::new("row", Span::mixed_site());
+    let alias = Ident::new("Output", Span::mixed_site());
+
+    let query_expr = query_expr(input, &info);
+
+    quote! {{
+        #[allow(dead_code)]
+        fn check_fields(#row: ( #( #domain_types, )* )) -> #ty {
+            type #alias = #ty;
+            #alias { #( #fields: #row.#indexes, )* }
+        }
+
+        const _: () = ::sqlitemapper::from_row::assert_fields::<#ty>(&[ #( #names, )* ]);
+
+        #query_expr
+    }}.into()
+}
+
+fn query_expr(input: QueryInput, info: &QueryInfo) -> TokenStream2 {
     let query = input.query;
 
-    let info = prepare_query(&query.value());
+    let row_type = row_type(&input.schema, info);
+    let named = named_params(&input.schema, &query, info, input.args);
+
+    let params_type = match &named {
+        Some(named) => named.params_type.clone(),
+        None => params_type(&input.schema, info),
+    };
 
-    let row_type = row_type(&input.schema, &info);
+    let sql = match info.stripped_sql() {
+        Some(sql) => LitStr::new(sql, query.span()),
+        None => query.clone(),
+    };
 
-    let output: TokenStream2 = quote! {
-        ::sqlitemapper::Query::<#row_type>::new_unchecked(#query)
+    let query_expr = if is_execute(info) {
+        quote! {
+            ::sqlitemapper::Execute::<#params_type>::new_unchecked(#sql)
+        }
+    } else {
+        quote! {
+            ::sqlitemapper::Query::<#row_type, #params_type>::new_unchecked(#sql)
+        }
     };
 
-    output.into()
+    match named {
+        Some(NamedParams { items, values: Some(values), .. }) => quote! {{
+            #items
+            #query_expr.bind(#values)
+        }},
+        Some(NamedParams { items, values: None, .. }) => quote! {{
+            #items
+            #query_expr
+        }},
+        None => query_expr,
+    }
+}
+
+/// Struct generated for the named parameters of a query, with a field for
+/// each name. Instantiated with the parameters' types it is the query's
+/// parameter list, and instantiated with values it binds them. Tuples of
+/// values in index order bind too, as for positional parameters.
+struct NamedParams {
+    /// Definition of the struct and its impls
+    items: TokenStream2,
+    params_type: TokenStream2,
+    /// Values to bind, unless the query was left unbound with `_`
+    values: Option<TokenStream2>,
+}
+
+/// Generates the params struct for a query's named parameters, if it has any.
+/// Queries with positional parameters are left with a list of their types.
+fn named_params(schema: &syn::Path, query: &LitStr, info: &QueryInfo, args: Vec<NamedArg>) -> Option<NamedParams> {
+    let named = info.params()
+        .iter()
+        .filter(|param| is_named(param))
+        .count();
+
+    if named == 0 {
+        if let Some(arg) = args.first() {
+            abort!(arg.span(), "named argument given but query has no named parameters");
+        }
+
+        return None;
+    }
+
+    if named != info.params().len() {
+        abort!(query.span(), "query mixes named and positional parameters, this is unsupported");
+    }
+
+    let names = info.params()
+        .iter()
+        .filter_map(|param| param.name())
+        .collect::<Vec<_>>();
+
+    let idents = names.iter()
+        .map(|name| {
+            let ident = &name[1..];
+            syn::parse_str::<Ident>(ident).unwrap_or_else(|_| {
+                abort!(query.span(), "parameter {} is not a valid Rust identifier", name);
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for (i, ident) in idents.iter().enumerate() {
+        if let Some(j) = idents[..i].iter().position(|other| other == ident) {
+            abort!(query.span(), "parameters {} and {} bind to the same Rust name", names[j], names[i]);
+        }
+    }
+
+    let struct_ident = Ident::new("Params", Span::mixed_site());
+    let stmt = Ident::new("stmt", Span::mixed_site());
+
+    let param_generics = (0..idents.len())
+        .map(|i| Ident::new(&format!("P{i}"), Span::mixed_site()))
+        .collect::<Vec<_>>();
+
+    let value_generics = (0..idents.len())
+        .map(|i| Ident::new(&format!("V{i}"), Span::mixed_site()))
+        .collect::<Vec<_>>();
+
+    let indexes = (1..=idents.len()).collect::<Vec<_>>();
+    let tuple_indexes = (0..idents.len()).map(syn::Index::from);
+    let count = idents.len();
+
+    let items = quote! {
+        #[allow(non_camel_case_types)]
+        struct #struct_ident<#( #param_generics, )*> {
+            #( #idents: #param_generics, )*
+        }
+
+        impl<#( #param_generics, )*> ::sqlitemapper::types::ParamList for #struct_ident<#( #param_generics, )*> {
+            const N: usize = #count;
+        }
+
+        impl<#( #param_generics, #value_generics: ::sqlitemapper::types::param::BindParam<#param_generics>, )*>
+            ::sqlitemapper::types::param::BindParams<#struct_ident<#( #param_generics, )*>>
+            for #struct_ident<#( #value_generics, )*>
+        {
+            fn bind_params(self, #stmt: &mut ::sqlitemapper::rusqlite::Statement) -> ::core::result::Result<(), ::sqlitemapper::rusqlite::Error> {
+                #( self.#idents.bind_param(#stmt, #indexes)?; )*
+                Ok(())
+            }
+        }
+
+        impl<#( #param_generics, #value_generics: ::sqlitemapper::types::param::BindParam<#param_generics>, )*>
+            ::sqlitemapper::types::param::BindParams<#struct_ident<#( #param_generics, )*>>
+            for ( #( #value_generics, )* )
+        {
+            fn bind_params(self, #stmt: &mut ::sqlitemapper::rusqlite::Statement) -> ::core::result::Result<(), ::sqlitemapper::rusqlite::Error> {
+                #( self.#tuple_indexes.bind_param(#stmt, #indexes)?; )*
+                Ok(())
+            }
+        }
+    };
+
+    let param_types = info.params()
+        .iter()
+        .map(|param| param_type(schema, param));
+
+    let params_type = quote! { #struct_ident<#( #param_types, )*> };
+
+    let unbound = args.iter().find(|arg| matches!(arg, NamedArg::Unbound(_)));
+
+    let values = match unbound {
+        Some(_) if args.len() > 1 => {
+            abort!(args[1].span(), "no other arguments allowed with _, which leaves the query unbound");
+        }
+        Some(_) => None,
+        None => Some(named_values(query, &names, &idents, &struct_ident, args)),
+    };
+
+    Some(NamedParams { items, params_type, values })
+}
+
+/// Generates the params struct holding the values for a query's named
+/// parameters given as arguments to the macro. Parameters without an
+/// argument are captured from variables of the same name in scope, as
+/// format! does, or taken from the fields of a `..expr` argument.
+fn named_values(query: &LitStr, names: &[&str], idents: &[Ident], struct_ident: &Ident, args: Vec<NamedArg>) -> TokenStream2 {
+    let mut values = vec![None; idents.len()];
+    let mut rest = None;
+
+    for arg in args {
+        match arg {
+            NamedArg::Value { ident, value } => {
+                let Some(idx) = idents.iter().position(|param| *param == ident) else {
+                    emit_error!(ident.span(), "query has no parameter named {}", ident);
+                    continue;
+                };
+
+                if values[idx].is_some() {
+                    emit_error!(ident.span(), "duplicate argument for parameter {}", names[idx]);
+                    continue;
+                }
+
+                values[idx] = Some(match value {
+                    Some((_, expr)) => quote! { #expr },
+                    None => quote! { #ident },
+                });
+            }
+            NamedArg::Rest { dots, expr } => {
+                if rest.is_some() {
+                    emit_error!(dots.spans[0], "only one ..expr argument allowed");
+                    continue;
+                }
+
+                rest = Some(expr);
+            }
+            NamedArg::Unbound(_) => unreachable!("_ is only allowed alone"),
+        }
+    }
+
+    let rest_ident = Ident::new("params", Span::mixed_site());
+
+    let values = values.into_iter()
+        .zip(idents)
+        .map(|(value, ident)| match (value, &rest) {
+            (Some(value), _) => value,
+            (None, Some(_)) => quote! { #rest_ident.#ident },
+            // capture variable from the caller's scope, as format! does
+            (None, None) => {
+                let ident = Ident::new(&ident.to_string(), query.span());
+                quote! { #ident }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match rest {
+        Some(rest) => quote! {{
+            let #rest_ident = #rest;
+            #struct_ident { #( #idents: #values, )* }
+        }},
+        None => quote! { #struct_ident { #( #idents: #values, )* } },
+    }
+}
+
+fn is_named(param: &QueryParam) -> bool {
+    param.name().is_some_and(|name| !name.starts_with('?'))
+}
+
+impl NamedArg {
+    fn span(&self) -> Span {
+        match self {
+            NamedArg::Value { ident, .. } => ident.span(),
+            NamedArg::Rest { dots, .. } => dots.spans[0],
+            NamedArg::Unbound(underscore) => underscore.span,
+        }
+    }
+}
+
+/// Statements which write to the database and return no rows are executed
+/// rather than queried
+fn is_execute(info: &QueryInfo) -> bool {
+    !info.is_readonly() && info.columns().is_empty()
 }
 
 fn row_type(schema: &syn::Path, info: &QueryInfo) -> TokenStream2 {
     info.columns()
         .iter()
         .rev()
-        .map(|col| column_path(schema, col))
+        .map(|col| column_type(schema, col))
         .fold(quote! { () }, |tail, path| {
             quote!{ ::sqlitemapper::types::ColumnCons<#path, #tail> }
         })
 }
 
+fn params_type(schema: &syn::Path, info: &QueryInfo) -> TokenStream2 {
+    info.params()
+        .iter()
+        .rev()
+        .map(|param| param_type(schema, param))
+        .fold(quote! { () }, |tail, param| {
+            quote!{ ::sqlitemapper::types::ParamCons<#param, #tail> }
+        })
+}
+
+fn param_type(schema: &syn::Path, param: &QueryParam) -> TokenStream2 {
+    match param.type_() {
+        ParamType::Column(origin) => {
+            let path = origin_column_path(schema, origin);
+            quote! { ::sqlitemapper::types::param::ColumnParam<#path> }
+        }
+        ParamType::Compared(origin) => {
+            let path = origin_column_path(schema, origin);
+            quote! { ::sqlitemapper::types::param::ComparedParam<#path> }
+        }
+        ParamType::Integer => {
+            quote! { ::sqlitemapper::types::param::SqlParam<::sqlitemapper::types::sql::Integer> }
+        }
+        ParamType::Unknown => {
+            quote! { ::sqlitemapper::types::param::Untyped }
+        }
+    }
+}
+
+fn origin_column_path(schema: &syn::Path, origin: &ColumnOrigin) -> TokenStream2 {
+    table_column_path(schema, &origin.database, &origin.table, &origin.column)
+}
+
 fn column_path(schema: &syn::Path, column: &ResultColumn) -> TokenStream2 {
     let (Some(table_name), Some(column_name), Some(schema_name))
         = (column.origin_table(), column.origin_column(), column.origin_database())
         else {
-            let name = column.describe();
-            abort_call_site!("{} is an expression, this is unsupported", name);
+            return expr_column(column);
         };
 
-    if schema_name != "main" {
+    table_column_path(schema, schema_name, table_name, column_name)
+}
+
+/// Path to a column's type in the mod `schema!` generates for its table,
+/// nested in a mod for the database if attached
+fn table_column_path(schema: &syn::Path, database: &str, table: &str, column: &str) -> TokenStream2 {
+    if database != "main" && !crate::schema::current().attached().any(|name| name == database) {
+        abort_call_site!("table {} is from database {}, which is not attached to the schema", table, database);
+    }
+
+    let table = Ident::new_raw(table, Span::mixed_site());
+    let column = Ident::new_raw(column, Span::mixed_site());
+
+    if database == "main" {
+        return quote! { #schema::#table::columns::#column };
+    }
+
+    let database = Ident::new_raw(database, Span::mixed_site());
+    quote! { #schema::#database::#table::columns::#column }
+}
+
+fn column_type(schema: &syn::Path, column: &ResultColumn) -> TokenStream2 {
+    match column.annotation() {
+        Some(annotation) => annotated_column(schema, column, annotation),
+        None if column.is_outer_joined() => {
+            let path = column_path(schema, column);
+            quote! {
+                ::sqlitemapper::types::Expr<
+                    ::sqlitemapper::types::sql::Nullable<<#path as ::sqlitemapper::types::Column>::SqlType>,
+                    ::core::option::Option<<#path as ::sqlitemapper::types::Column>::DomainType>,
+                >
+            }
+        }
+        None => column_path(schema, column),
+    }
+}
+
+/// Column read as the type given in its alias, as in `AS "n: i64"`, with
+/// nullability overridden by `!` or `?`. The SQL type comes from the
+/// column or expression where known, otherwise from the Rust type.
+fn annotated_column(schema: &syn::Path, column: &ResultColumn, annotation: &Annotation) -> TokenStream2 {
+    let rust_type = annotation.rust_type.as_ref().map(|ty| {
+        syn::parse_str::<syn::Type>(ty).unwrap_or_else(|_| {
+            abort_call_site!("{} is annotated with invalid Rust type `{}`", column.describe(), ty);
+        })
+    });
+
+    // columns on the optional side of an outer join are nullable unless
+    // annotated otherwise
+    let nullability = match annotation.nullability {
+        None if column.is_outer_joined() => Some(Nullability::Nullable),
+        nullability => nullability,
+    };
+
+    let sql_type = if column.origin_column().is_some() {
+        let path = column_path(schema, column);
+        let sql_type = quote! { <#path as ::sqlitemapper::types::Column>::SqlType };
+
+        match nullability {
+            Some(Nullability::NonNull) => quote! { <#sql_type as ::sqlitemapper::types::sql::Nullability>::NonNull },
+            Some(Nullability::Nullable) => quote! { <#sql_type as ::sqlitemapper::types::sql::Nullability>::Nullable },
+            None => sql_type,
+        }
+    } else if let Some(expr_type) = column.expr_type() {
+        let nullable = match nullability {
+            Some(nullability) => nullability == Nullability::Nullable,
+            None => expr_type.nullable,
+        };
+
+        expr_sql_type(&ExprType { nullable, ..*expr_type })
+    } else if let Some(rust_type) = &rust_type {
+        let sql_type = quote! { <#rust_type as ::sqlitemapper::types::DefaultSqlType>::SqlType };
+
+        match nullability {
+            Some(Nullability::Nullable) => quote! { ::sqlitemapper::types::sql::Nullable<#sql_type> },
+            _ => sql_type,
+        }
+    } else {
         let name = column.describe();
-        abort_call_site!("{} is from foreign schema {}, this is unsupported", name, schema_name);
+        abort_call_site!("{} is an expression whose type could not be inferred, annotate it with a Rust type as in `AS \"{}: i64\"`",
+            name, column.name().unwrap_or("name"));
+    };
+
+    match (rust_type, nullability) {
+        (Some(ty), Some(Nullability::Nullable)) => quote! { ::sqlitemapper::types::Expr<#sql_type, ::core::option::Option<#ty>> },
+        (Some(ty), _) => quote! { ::sqlitemapper::types::Expr<#sql_type, #ty> },
+        (None, _) => quote! { ::sqlitemapper::types::Expr<#sql_type> },
     }
+}
+
+/// Synthetic column for a result computed by an expression
+fn expr_column(column: &ResultColumn) -> TokenStream2 {
+    let Some(expr_type) = column.expr_type() else {
+        let name = column.describe();
+        abort_call_site!("{} is an expression whose type could not be inferred, annotate it with a Rust type as in `AS \"{}: i64\"`",
+            name, column.name().unwrap_or("name"));
+    };
 
-    let table = Ident::new_raw(table_name, Span::mixed_site());
-    let column = Ident::new_raw(column_name, Span::mixed_site());
+    let sql_type = expr_sql_type(expr_type);
+
+    quote! { ::sqlitemapper::types::Expr<#sql_type> }
+}
+
+fn expr_sql_type(expr_type: &ExprType) -> TokenStream2 {
+    let sql_type = match expr_type.value_type {
+        ValueType::Integer => quote! { ::sqlitemapper::types::sql::Integer },
+        ValueType::Real => quote! { ::sqlitemapper::types::sql::Real },
+        ValueType::Text => quote! { ::sqlitemapper::types::sql::Text },
+        ValueType::Blob => quote! { ::sqlitemapper::types::sql::Blob },
+    };
 
-    quote! {
-        #schema::#table::columns::#column
+    match expr_type.nullable {
+        true => quote! { ::sqlitemapper::types::sql::Nullable<#sql_type> },
+        false => sql_type,
     }
 }
 
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use lazy_static::lazy_static;
-use proc_macro_error::{abort_call_site, emit_error, emit_warning};
+use proc_macro_error::{abort, abort_call_site, emit_error, emit_warning};
 use proc_macro2::{TokenStream as TokenStream2, Span, Ident, Group, Delimiter};
 use quote::spanned::Spanned;
 use quote::{quote, ToTokens};
@@ -11,7 +11,7 @@ use syn::{ItemMod, ItemType, Type, Visibility};
 use syn::{Item, parse_quote};
 use thiserror::Error;
 
-use sqlitemapper_schema::{Schema, LoadError, TableColumn};
+use sqlitemapper_schema::{Affinity, Schema, LoadError, TableColumn};
 
 lazy_static! {
     static ref SCHEMA: Result<Schema, SchemaError> = load_from_env();
@@ -23,6 +23,8 @@ pub enum SchemaError {
     EnvVarNotSet,
     #[error("Error loading schema from {path}: {error}")]
     Load { path: PathBuf, error: LoadError },
+    #[error("Invalid SQLITEMAPPER_ATTACH entry {0:?}, expected name=path")]
+    InvalidAttach(String),
 }
 
 pub fn try_current() -> Result<&'static Schema, &'static SchemaError> {
@@ -42,26 +44,46 @@ fn load_from_env() -> Result<Schema, SchemaError> {
 
     let path = PathBuf::from(path);
 
-    Schema::from_file(&path)
-        .map_err(|error| SchemaError::Load { path, error })
+    let mut schema = Schema::from_file(&path)
+        .map_err(|error| SchemaError::Load { path, error })?;
+
+    // databases the application attaches, as a comma separated list of
+    // name=path, eg. SQLITEMAPPER_ATTACH="archive=schema/archive.sql"
+    let attach = std::env::var("SQLITEMAPPER_ATTACH").unwrap_or_default();
+
+    for entry in attach.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (name, path) = entry.split_once('=')
+            .ok_or_else(|| SchemaError::InvalidAttach(entry.to_owned()))?;
+
+        let path = PathBuf::from(path.trim());
+
+        schema.attach_file(name.trim(), &path)
+            .map_err(|error| SchemaError::Load { path, error })?;
+    }
+
+    Ok(schema)
 }
 
 pub type SchemaInput = ItemMod;
 
 pub fn schema_impl(input: ItemMod) -> ItemMod {
     let schema = current();
-    let schema_mod_decl = parse_schema_mod(input);
+    let attached = schema.attached().collect::<Vec<_>>();
+    let schema_mod_decl = parse_schema_mod(input, &attached);
     generate_schema_mod(schema, schema_mod_decl)
 }
 
 struct SchemaModDecl {
     item: ItemMod,
     table_mods: HashMap<String, TableModDecl>,
+    /// Mods for attached databases, containing their table mods
+    database_mods: HashMap<String, SchemaModDecl>,
     unknown_items: Vec<Item>,
 }
 
-fn parse_schema_mod(mut item: ItemMod) -> SchemaModDecl {
+fn parse_schema_mod(mut item: ItemMod, attached: &[&str]) -> SchemaModDecl {
     let mut table_mods = HashMap::new();
+    let mut database_mods = HashMap::new();
     let mut unknown_items = Vec::new();
 
     let items = item.content.take()
@@ -73,11 +95,16 @@ fn parse_schema_mod(mut item: ItemMod) -> SchemaModDecl {
             Item::Mod(item) => {
                 let name = item.ident.to_string();
 
-                if table_mods.contains_key(&name) {
+                if table_mods.contains_key(&name) || database_mods.contains_key(&name) {
                     emit_error!(item.__span(), "Duplicate mod definition");
                     continue;
                 }
 
+                if attached.contains(&name.as_str()) {
+                    database_mods.insert(name, parse_schema_mod(item, &[]));
+                    continue;
+                }
+
                 table_mods.insert(name, parse_table_mod(item));
             }
             _ => {
@@ -87,7 +114,7 @@ fn parse_schema_mod(mut item: ItemMod) -> SchemaModDecl {
         }
     }
 
-    SchemaModDecl { item, table_mods, unknown_items }
+    SchemaModDecl { item, table_mods, database_mods, unknown_items }
 }
 
 #[derive(Default)]
@@ -175,25 +202,40 @@ fn parse_column_type_alias(item: ItemType) -> ColumnTypeAliasDecl {
 }
 
 fn generate_schema_mod(schema: &Schema, mut decl: SchemaModDecl) -> ItemMod {
-    let tables = schema.tables().unwrap_or_else(|err| {
-        abort_call_site!("Error listing SQLite tables: {}", err);
-    });
-
     let (brace, mut items) = decl.item.content
+        .take()
         .map(|(brace, items)| (Some(brace), items))
         .unwrap_or_default();
 
-    for table in tables {
-        let table_decl = decl.table_mods.remove(&table);
-        let table_mod = generate_table_mod(schema, &table, table_decl);
-        items.push(Item::Mod(table_mod));
-    }
+    items.extend(generate_table_mods(schema, "main", &mut decl));
 
-    for (name, table_decl) in decl.table_mods {
-        let span = table_decl.ident.__span();
-        emit_error!(span, "No table {:?} found, only mods corresponding to SQLite tables allowed in schema mod", name);
+    for database in schema.attached() {
+        let mut database_decl = decl.database_mods.remove(database)
+            .unwrap_or_else(|| SchemaModDecl {
+                item: parse_quote! { pub mod _database; },
+                table_mods: HashMap::new(),
+                database_mods: HashMap::new(),
+                unknown_items: Vec::new(),
+            });
+
+        let ident = Ident::new_raw(database, database_decl.item.ident.span());
+
+        if items.iter().any(|item| matches!(item, Item::Mod(table) if table.ident == ident)) {
+            abort_call_site!("Attached database {:?} has the same name as a table in the main database", database);
+        }
+
+        let table_mods = token_stream(generate_table_mods(schema, database, &mut database_decl));
+        let unknown_items = token_stream(database_decl.unknown_items);
+
+        items.push(parse_quote! {
+            pub mod #ident {
+                #table_mods
+                #unknown_items
+            }
+        });
     }
 
+    items.extend(generate_verify(schema));
     items.extend(decl.unknown_items);
 
     let tokens = items.iter()
@@ -215,17 +257,161 @@ fn generate_schema_mod(schema: &Schema, mut decl: SchemaModDecl) -> ItemMod {
     }
 }
 
+/// Generates a mod for each table of the main or an attached database
+fn generate_table_mods(schema: &Schema, database: &str, decl: &mut SchemaModDecl) -> Vec<Item> {
+    let tables = schema.tables_in(database).unwrap_or_else(|err| {
+        abort_call_site!("Error listing SQLite tables: {}", err);
+    });
+
+    let views = schema.views_in(database).unwrap_or_else(|err| {
+        abort_call_site!("Error listing SQLite views: {}", err);
+    });
+
+    let mut items = Vec::new();
+
+    for table in tables {
+        let table_decl = decl.table_mods.remove(&table);
+        let table_mod = generate_table_mod(schema, database, &table, table_decl, TableKind::Table);
+        items.push(Item::Mod(table_mod));
+    }
+
+    for view in views {
+        let view_decl = decl.table_mods.remove(&view);
+        let view_mod = generate_table_mod(schema, database, &view, view_decl, TableKind::View);
+        items.push(Item::Mod(view_mod));
+    }
+
+    for (name, table_decl) in decl.table_mods.drain() {
+        let span = table_decl.ident.__span();
+        emit_error!(span, "No table or view {:?} found, only mods corresponding to SQLite tables allowed in schema mod", name);
+    }
+
+    items
+}
+
+/// Generates the list of tables and views in the schema with its
+/// fingerprint, and a `verify` function comparing a database against them
+/// at runtime
+fn generate_verify(schema: &Schema) -> Vec<Item> {
+    let databases = std::iter::once("main").chain(schema.attached());
+
+    let mut tables = Vec::new();
+
+    for database in databases {
+        let mut names = schema.tables_in(database).unwrap_or_else(|err| {
+            abort_call_site!("Error listing SQLite tables: {}", err);
+        });
+
+        names.retain(|name| !name.starts_with("sqlite_"));
+        names.sort();
+
+        for name in names {
+            let columns = schema.columns_in(database, &name).unwrap_or_else(|err| {
+                abort_call_site!("Error listing columns for SQLite table {:?}: {}", name, err);
+            });
+
+            let columns = columns.iter().map(|column| {
+                let TableColumn { name, type_, not_null, .. } = column;
+
+                quote! {
+                    ::sqlitemapper::verify::ColumnInfo { name: #name, type_: #type_, not_null: #not_null }
+                }
+            });
+
+            tables.push(quote! {
+                ::sqlitemapper::verify::TableInfo {
+                    database: #database,
+                    name: #name,
+                    view: false,
+                    columns: &[#(#columns),*],
+                }
+            });
+        }
+
+        let mut views = schema.views_in(database).unwrap_or_else(|err| {
+            abort_call_site!("Error listing SQLite views: {}", err);
+        });
+
+        views.sort();
+
+        for name in views {
+            let columns = schema.view_columns(database, &name).unwrap_or_else(|err| {
+                abort_call_site!("Error listing columns for SQLite view {:?}: {}", name, err);
+            });
+
+            // only the names of view columns are verified
+            let columns = columns.iter().map(|column| {
+                let name = &column.name;
+
+                quote! {
+                    ::sqlitemapper::verify::ColumnInfo { name: #name, type_: "", not_null: false }
+                }
+            });
+
+            tables.push(quote! {
+                ::sqlitemapper::verify::TableInfo {
+                    database: #database,
+                    name: #name,
+                    view: true,
+                    columns: &[#(#columns),*],
+                }
+            });
+        }
+    }
+
+    vec![
+        parse_quote! {
+            /// Tables, views and columns of the schema this mod was
+            /// generated from
+            pub const TABLES: &[::sqlitemapper::verify::TableInfo] = &[#(#tables),*];
+        },
+        parse_quote! {
+            /// Fingerprint of `TABLES`, which changes with the schema
+            pub const FINGERPRINT: u64 = ::sqlitemapper::verify::fingerprint(TABLES);
+        },
+        parse_quote! {
+            /// Compares the database with the schema this mod was generated
+            /// from, returning any differences which would break its queries
+            pub fn verify(conn: &::sqlitemapper::Connection) -> ::core::result::Result<::sqlitemapper::verify::SchemaDiff, ::sqlitemapper::Error> {
+                ::sqlitemapper::verify::verify(conn, TABLES)
+            }
+        },
+    ]
+}
+
 fn token_stream<T: ToTokens>(items: impl IntoIterator<Item = T>) -> TokenStream2 {
     items.into_iter()
         .map(|item| item.to_token_stream())
         .collect()
 }
 
-fn generate_table_mod(schema: &Schema, table: &str, mut decl: Option<TableModDecl>) -> ItemMod {
-    let columns = schema.columns(table).unwrap_or_else(|err| {
+#[derive(PartialEq, Eq)]
+enum TableKind {
+    Table,
+    /// Views are read only, so get no insert function
+    View,
+}
+
+fn generate_table_mod(schema: &Schema, database: &str, table: &str, mut decl: Option<TableModDecl>, kind: TableKind) -> ItemMod {
+    let columns = match kind {
+        TableKind::Table => schema.columns_in(database, table),
+        TableKind::View => schema.view_columns(database, table),
+    };
+
+    let columns = columns.unwrap_or_else(|err| {
         abort_call_site!("Error listing columns for SQLite table {:?}: {}", table, err);
     });
 
+    let table_name_span = decl.as_ref()
+        .map(|decl| decl.ident.__span())
+        .unwrap_or(Span::call_site());
+
+    if kind == TableKind::View {
+        if let Some(column) = columns.iter().find(|col| col.type_.is_empty()) {
+            abort!(table_name_span, "Could not infer the type of column {:?} of view {:?}, add a CAST to its expression", column.name, table);
+        }
+    }
+
     let mut column_types = Vec::<ItemType>::new();
     let mut column_defns = Vec::<Item>::new();
 
@@ -235,7 +421,7 @@ fn generate_table_mod(schema: &Schema, table: &str, mut decl: Option<TableModDec
 
         let column_ident = Ident::new_raw(&column.name, Span::mixed_site());
 
-        let sql_ty = generate_column_sql_type(&column);
+        let sql_ty = generate_column_sql_type(column);
 
         let rust_ty: Box<Type> = column_decl.as_ref()
             .map(|decl| decl.ty.clone())
@@ -262,10 +448,10 @@ fn generate_table_mod(schema: &Schema, table: &str, mut decl: Option<TableModDec
     let column_types = token_stream(column_types);
     let column_defns = token_stream(column_defns);
 
-    let table_name_span = decl.as_ref()
-        .map(|decl| decl.ident.__span())
-        .unwrap_or(Span::call_site());
-
+    let insert_fn = match kind {
+        TableKind::Table => generate_insert(database, table, &columns),
+        TableKind::View => quote! {},
+    };
     let table = Ident::new_raw(table, table_name_span);
 
     let unknown_items = decl.iter()
@@ -281,6 +467,7 @@ fn generate_table_mod(schema: &Schema, table: &str, mut decl: Option<TableModDec
                 #column_defns
             }
             #record_structs
+            #insert_fn
             #column_types
             #unknown_items
         }
@@ -288,13 +475,19 @@ fn generate_table_mod(schema: &Schema, table: &str, mut decl: Option<TableModDec
 }
 
 fn generate_column_sql_type(column: &TableColumn) -> Box<Type> {
-    let inherent_type = match column.type_.as_str() {
-        | "INT"
-        | "INTEGER" => quote! { ::sqlitemapper::types::sql::Integer },
-        | "REAL"    => quote! { ::sqlitemapper::types::sql::Real },
-        | "TEXT"    => quote! { ::sqlitemapper::types::sql::Text },
-        | "BLOB"    => quote! { ::sqlitemapper::types::sql::Blob },
-        _ => { abort_call_site!("unknown sqlite datatype: {}", column.type_); }
+    let inherent_type = match column.affinity() {
+        _ if column.is_any() => quote! { ::sqlitemapper::types::sql::Any },
+        _ if column.boolean => quote! { ::sqlitemapper::types::sql::Boolean },
+        Affinity::Integer => quote! { ::sqlitemapper::types::sql::Integer },
+        Affinity::Real    => quote! { ::sqlitemapper::types::sql::Real },
+        Affinity::Text    => quote! { ::sqlitemapper::types::sql::Text },
+        Affinity::Blob    => quote! { ::sqlitemapper::types::sql::Blob },
+        Affinity::Numeric => quote! { ::sqlitemapper::types::sql::Numeric },
+    };
+
+    let inherent_type = match column.strict {
+        true => quote! { ::sqlitemapper::types::sql::Strict<#inherent_type> },
+        false => inherent_type,
     };
 
     let type_ = match column.not_null {
@@ -311,8 +504,8 @@ fn get_single_primary_key(columns: &[TableColumn]) -> Option<&TableColumn> {
 
     let single = columns.next()?;
     match columns.next() {
-        None => { return Some(single); }
-        Some(_) => {return None; }
+        None => Some(single),
+        Some(_) => None,
     }
 }
 
@@ -321,16 +514,7 @@ fn primary_key_auto_assignable(columns: &[TableColumn]) -> bool {
         return false;
     };
 
-    if pkey.type_ == "INTEGER" {
-        // alias for rowid
-        return true;
-    }
-
-    if pkey.has_default {
-        return true;
-    }
-
-    false
+    pkey.rowid_alias || pkey.has_default
 }
 
 fn generate_record_structs(columns: &[TableColumn]) -> TokenStream2 {
@@ -359,38 +543,112 @@ fn generate_record_structs(columns: &[TableColumn]) -> TokenStream2 {
         quote!{}
     };
 
+    let record_from_row = generate_record_from_row(columns);
+
     quote!{
         #record_struct
+        #record_from_row
         #new_record_struct
     }
 }
 
-fn generate_record_field(column: &TableColumn) -> TokenStream2 {
-    let ident = Ident::new_raw(&column.name, Span::call_site());
-    quote! { pub #ident: <columns::#ident as ::sqlitemapper::types::Column>::DomainType, }
-}
-
-fn table_primary_key(columns: &[TableColumn]) -> TokenStream2 {
-    let mut pkeys = columns.iter()
-        .filter(|column| column.primary_key_part.is_some())
+/// Reads a Record from query rows made up of exactly the table's columns in
+/// order, as selected by `SELECT *` or `SELECT table.*`
+fn generate_record_from_row(columns: &[TableColumn]) -> TokenStream2 {
+    let idents = columns.iter()
+        .map(|col| Ident::new_raw(&col.name, Span::call_site()))
         .collect::<Vec<_>>();
 
-    pkeys.sort_by_key(|col| col.primary_key_part);
+    let row_type = idents.iter()
+        .rev()
+        .fold(quote! { () }, |tail, ident| quote! {
+            ::sqlitemapper::types::ColumnCons<columns::#ident, #tail>
+        });
+
+    let reader = Ident::new("reader", Span::mixed_site());
 
-    // don't generate a pkey type if there are no pkeys
-    if pkeys.len() == 0 {
-        return quote!{}
+    quote! {
+        impl ::sqlitemapper::from_row::FromRow<#row_type> for Record {
+            fn from_row<'a>(
+                #reader: ::sqlitemapper::from_row::RowReader<'a, #row_type>,
+            ) -> ::core::result::Result<(Self, ::sqlitemapper::from_row::RowReader<'a, ()>), ::sqlitemapper::rusqlite::Error> {
+                #(
+                    let (#idents, #reader) = #reader.next()?;
+                )*
+
+                Ok((Record { #( #idents, )* }, #reader))
+            }
+        }
     }
+}
 
-    let fields = pkeys.iter()
-        .map(|pkey| {
-            let name = Ident::new_raw(&pkey.name, Span::mixed_site());
-            quote! { pub #name, }
-        })
-        .collect::<TokenStream2>();
+fn generate_insert(database: &str, table: &str, columns: &[TableColumn]) -> TokenStream2 {
+    let (record, columns) = if primary_key_auto_assignable(columns) {
+        let columns = columns.iter()
+            .filter(|col| col.primary_key_part.is_none())
+            .collect::<Vec<_>>();
+
+        (quote! { NewRecord }, columns)
+    } else {
+        (quote! { Record }, columns.iter().collect())
+    };
+
+    let column_names = columns.iter()
+        .map(|col| quote_sql_ident(&col.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let placeholders = (1..=columns.len())
+        .map(|i| format!("?{i}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let table = match database {
+        "main" => quote_sql_ident(table),
+        _ => format!("{}.{}", quote_sql_ident(database), quote_sql_ident(table)),
+    };
+
+    let sql = format!("INSERT INTO {} ({}) VALUES ({})", table, column_names, placeholders);
+
+    // rows of WITHOUT ROWID tables have no rowid to return
+    let output = match columns.first().is_some_and(|col| col.without_rowid) {
+        true => quote! { () },
+        false => quote! { i64 },
+    };
+
+    let idents = columns.iter()
+        .map(|col| Ident::new_raw(&col.name, Span::call_site()))
+        .collect::<Vec<_>>();
+
+    let params = idents.iter()
+        .rev()
+        .fold(quote! { () }, |tail, ident| quote! {
+            ::sqlitemapper::types::ParamCons<::sqlitemapper::types::param::ColumnParam<columns::#ident>, #tail>
+        });
 
     quote! {
-        // #[derive(Debug, Clone, PartialEq, PartialOrd)]
-        pub struct Id(#fields);
+        impl ::sqlitemapper::insert::InsertRecord for #record {
+            type Params = #params;
+            type Values = ( #( <columns::#idents as ::sqlitemapper::types::Column>::DomainType, )* );
+            type Output = #output;
+
+            fn into_values(self) -> Self::Values {
+                ( #( self.#idents, )* )
+            }
+        }
+
+        pub fn insert(record: #record) -> ::sqlitemapper::insert::Insert<#record> {
+            ::sqlitemapper::insert::Insert::new_unchecked(#sql, record)
+        }
     }
 }
+
+fn quote_sql_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn generate_record_field(column: &TableColumn) -> TokenStream2 {
+    let ident = Ident::new_raw(&column.name, Span::call_site());
+    quote! { pub #ident: <columns::#ident as ::sqlitemapper::types::Column>::DomainType, }
+}
+
@@ -0,0 +1,71 @@
+/// Type affinity of a column, the storage class SQLite prefers for its values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Affinity {
+    Integer,
+    Text,
+    Blob,
+    Real,
+    Numeric,
+}
+
+impl Affinity {
+    /// Determines the affinity of a declared column type by the rules SQLite
+    /// documents, applied in order:
+    ///
+    /// 1. containing "INT" is INTEGER
+    /// 2. containing "CHAR", "CLOB" or "TEXT" is TEXT
+    /// 3. containing "BLOB", or no type, is BLOB
+    /// 4. containing "REAL", "FLOA" or "DOUB" is REAL
+    /// 5. anything else is NUMERIC
+    pub fn of(decl_type: &str) -> Self {
+        let decl_type = decl_type.to_ascii_uppercase();
+        let contains = |names: &[&str]| names.iter().any(|name| decl_type.contains(name));
+
+        if contains(&["INT"]) {
+            Affinity::Integer
+        } else if contains(&["CHAR", "CLOB", "TEXT"]) {
+            Affinity::Text
+        } else if contains(&["BLOB"]) || decl_type.trim().is_empty() {
+            Affinity::Blob
+        } else if contains(&["REAL", "FLOA", "DOUB"]) {
+            Affinity::Real
+        } else {
+            Affinity::Numeric
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn affinity_rules() {
+        let cases = [
+            ("INTEGER", Affinity::Integer),
+            ("int", Affinity::Integer),
+            ("BIGINT", Affinity::Integer),
+            ("UNSIGNED BIG INT", Affinity::Integer),
+            // INT takes precedence, so this isn't REAL
+            ("FLOATING POINT", Affinity::Integer),
+            ("VARCHAR(255)", Affinity::Text),
+            ("nchar(55)", Affinity::Text),
+            ("CLOB", Affinity::Text),
+            ("TEXT", Affinity::Text),
+            ("BLOB", Affinity::Blob),
+            ("", Affinity::Blob),
+            ("REAL", Affinity::Real),
+            ("DOUBLE PRECISION", Affinity::Real),
+            ("float", Affinity::Real),
+            ("NUMERIC", Affinity::Numeric),
+            ("DECIMAL(10,5)", Affinity::Numeric),
+            ("BOOLEAN", Affinity::Numeric),
+            ("DATETIME", Affinity::Numeric),
+            ("STRING", Affinity::Numeric),
+        ];
+
+        for (decl_type, affinity) in cases {
+            assert_eq!(Affinity::of(decl_type), affinity, "{decl_type}");
+        }
+    }
+}
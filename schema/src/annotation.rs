@@ -0,0 +1,127 @@
+use crate::lexer::{Token, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nullability {
+    NonNull,
+    Nullable,
+}
+
+/// Type annotation given in the alias of a result column, as in
+/// `AS "n: i64"`, `AS "name!"` or `AS "name?: Name"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    /// Forces the column to be read as NOT NULL (`!`) or nullable (`?`)
+    pub nullability: Option<Nullability>,
+    /// Rust type to read the column as, unparsed
+    pub rust_type: Option<String>,
+}
+
+/// Splits an annotated column name into the plain name and its annotation.
+/// Returns None for names which carry no annotation.
+pub fn parse(name: &str) -> Option<(String, Annotation)> {
+    let (name, rust_type) = match name.split_once(':') {
+        Some((name, rust_type)) => (name.trim_end(), Some(rust_type.trim().to_owned())),
+        None => (name, None),
+    };
+
+    let (name, nullability) = if let Some(name) = name.strip_suffix('!') {
+        (name, Some(Nullability::NonNull))
+    } else if let Some(name) = name.strip_suffix('?') {
+        (name, Some(Nullability::Nullable))
+    } else {
+        (name, None)
+    };
+
+    if nullability.is_none() && rust_type.is_none() {
+        return None;
+    }
+
+    // expressions without an alias are named by their SQL text, which may
+    // contain the same characters
+    let is_ident = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+
+    if !is_ident || rust_type.as_deref() == Some("") {
+        return None;
+    }
+
+    Some((name.to_owned(), Annotation { nullability, rust_type }))
+}
+
+/// Rewrites SQL, replacing each alias token naming one of `annotated` with
+/// the plain name it maps to, so the annotations don't reach SQLite at
+/// runtime
+pub fn strip(sql: &str, tokens: &[Token], annotated: &[(&str, &str)]) -> String {
+    let mut stripped = String::with_capacity(sql.len());
+    let mut pos = 0;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        let text = match token.kind {
+            TokenKind::QuotedIdent => token.ident().unwrap_or_default().into_owned(),
+            // string literals are only names when used as an alias
+            TokenKind::String if idx > 0 && tokens[idx - 1].is_keyword("as") && token.text.len() > 1 => {
+                token.text[1..token.text.len() - 1].replace("''", "'")
+            }
+            _ => continue,
+        };
+
+        let Some((_, name)) = annotated.iter().find(|(annotated, _)| *annotated == text) else {
+            continue;
+        };
+
+        stripped.push_str(&sql[pos..token.start]);
+        stripped.push('"');
+        stripped.push_str(&name.replace('"', "\"\""));
+        stripped.push('"');
+        pos = token.start + token.text.len();
+    }
+
+    stripped.push_str(&sql[pos..]);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    fn annotation(nullability: Option<Nullability>, rust_type: Option<&str>) -> Annotation {
+        Annotation { nullability, rust_type: rust_type.map(str::to_owned) }
+    }
+
+    #[test]
+    fn parses_annotations() {
+        assert_eq!(parse("n: i64"), Some(("n".to_owned(), annotation(None, Some("i64")))));
+        assert_eq!(parse("name!"), Some(("name".to_owned(), annotation(Some(Nullability::NonNull), None))));
+        assert_eq!(
+            parse("name?: crate::Name"),
+            Some(("name".to_owned(), annotation(Some(Nullability::Nullable), Some("crate::Name")))),
+        );
+    }
+
+    #[test]
+    fn ignores_plain_names_and_expressions() {
+        assert_eq!(parse("name"), None);
+        assert_eq!(parse("count(*)"), None);
+        assert_eq!(parse("json_extract(x, '$.a')"), None);
+        assert_eq!(parse("n:"), None);
+        assert_eq!(parse("1!"), None);
+    }
+
+    #[test]
+    fn strips_annotated_aliases() {
+        let sql = r#"SELECT count(*) AS "n: i64", name AS 'name!', x AS "other" FROM t"#;
+        let stripped = strip(sql, &tokenize(sql), &[("n: i64", "n"), ("name!", "name")]);
+
+        assert_eq!(stripped, r#"SELECT count(*) AS "n", name AS "name", x AS "other" FROM t"#);
+    }
+
+    #[test]
+    fn leaves_string_literals() {
+        let sql = "SELECT 'name!' AS \"name!\" FROM t";
+        let stripped = strip(sql, &tokenize(sql), &[("name!", "name")]);
+
+        assert_eq!(stripped, "SELECT 'name!' AS \"name\" FROM t");
+    }
+}
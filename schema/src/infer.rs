@@ -0,0 +1,912 @@
+use rusqlite::Connection;
+
+use crate::affinity::Affinity;
+use crate::lexer::{Token, TokenKind};
+use crate::returning::{find_top_level, split_items};
+use crate::scope::{self, column_ref, ColumnOrigin, TableRef};
+
+/// Storage class of the values an expression produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Integer,
+    Real,
+    Text,
+    Blob,
+}
+
+/// Type of a result column computed by an expression rather than read
+/// directly from a table column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExprType {
+    pub value_type: ValueType,
+    pub nullable: bool,
+}
+
+/// What can be worked out about a result column from its expression
+#[derive(Debug, Clone, Default)]
+pub struct ColumnInfo {
+    /// Type of the expression computing the column, if it could be inferred
+    pub expr_type: Option<ExprType>,
+    /// Whether the column is read directly from a table on the optional side
+    /// of an outer join
+    pub outer_joined: bool,
+    /// Column the result column names directly. SQLite reports the origin
+    /// of columns read from tables, but not of columns of views which are
+    /// computed by an expression.
+    pub origin: Option<ColumnOrigin>,
+}
+
+/// Analyses each of the `count` result columns of a statement from the
+/// expressions in its result list. Nothing is known of columns whose items
+/// can't be matched up with the statement's columns.
+pub fn result_columns(conn: &Connection, tokens: &[Token], count: usize) -> Vec<ColumnInfo> {
+    let scope = scope::table_refs(tokens);
+
+    let Some(items) = result_list(tokens) else {
+        return vec![ColumnInfo::default(); count];
+    };
+
+    let mut columns = Vec::with_capacity(count);
+
+    for item in items {
+        if let Some(tables) = star_tables(&scope, item) {
+            for table in tables {
+                if let Some(subquery) = &table.subquery {
+                    // SQLite reports the tables the subquery selects from
+                    // as the origins of its columns
+                    let Some(subquery_count) = scope::subquery_column_count(conn, &tokens[subquery.clone()]) else {
+                        return vec![ColumnInfo::default(); count];
+                    };
+
+                    let column = ColumnInfo { expr_type: None, outer_joined: table.optional, origin: None };
+                    columns.extend(std::iter::repeat_n(column, subquery_count));
+                    continue;
+                }
+
+                let database = table.database.as_deref().unwrap_or("main");
+
+                for column in scope::table_column_names(conn, database, &table.name) {
+                    columns.push(ColumnInfo {
+                        expr_type: None,
+                        outer_joined: table.optional,
+                        origin: scope::lookup_column(conn, database, &table.name, &column),
+                    });
+                }
+            }
+            continue;
+        }
+
+        let mut parser = Parser { conn, scope: &scope, tokens: item, pos: 0 };
+
+        let expr_type = parser.expr(0)
+            .filter(|_| parser.at_alias())
+            .and_then(Typed::expr_type);
+
+        let column = column_ref(&item[..parser.pos]);
+        let resolved = column.as_ref().and_then(|column| column.resolve_table(conn, &scope));
+
+        let (outer_joined, origin) = match resolved {
+            Some((table, origin)) => (table.optional, Some(origin)),
+            None => (column.is_some_and(|column| column.maybe_outer_joined(&scope)), None),
+        };
+
+        columns.push(ColumnInfo { expr_type, outer_joined, origin });
+    }
+
+    if columns.len() != count {
+        return vec![ColumnInfo::default(); count];
+    }
+
+    columns
+}
+
+// keywords ending the result list of a SELECT
+const RESULT_LIST_END: &[&str] = &[
+    "from", "where", "group", "having", "window", "order", "limit",
+    "union", "intersect", "except",
+];
+
+/// Finds the items of the result list of the outermost SELECT, or of the
+/// RETURNING clause of a write
+fn result_list<'t, 'a>(tokens: &'t [Token<'a>]) -> Option<Vec<&'t [Token<'a>]>> {
+    if let Some(returning) = find_top_level(tokens, |t| t.is_keyword("returning")) {
+        return Some(split_items(&tokens[returning + 1..]));
+    }
+
+    let mut start = find_top_level(tokens, |t| t.is_keyword("select"))? + 1;
+
+    if tokens.get(start).is_some_and(|t| t.is_keyword("distinct") || t.is_keyword("all")) {
+        start += 1;
+    }
+
+    let rest = &tokens[start..];
+
+    let end = find_top_level(rest, |t| RESULT_LIST_END.iter().any(|kw| t.is_keyword(kw)))
+        .unwrap_or(rest.len());
+
+    Some(split_items(&rest[..end]))
+}
+
+/// Returns the tables a `*` or `table.*` result item selects the columns of
+fn star_tables<'s>(scope: &'s [TableRef], item: &[Token]) -> Option<Vec<&'s TableRef>> {
+    // tables of the outermost statement, not of subqueries
+    let tables = scope.iter().filter(|table| table.depth == 0);
+
+    match item {
+        [star] if star.is_punct("*") => Some(tables.collect()),
+        [.., qualifier, dot, star] if dot.is_punct(".") && star.is_punct("*") => {
+            let qualifier = qualifier.ident()?;
+            Some(tables.filter(|table| table.is_named(&qualifier)).take(1).collect())
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Known(ValueType),
+    /// The NULL literal, which takes on the type of whatever it's combined
+    /// with
+    Null,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Typed {
+    ty: Ty,
+    nullable: bool,
+}
+
+impl Typed {
+    const NULL: Typed = Typed { ty: Ty::Null, nullable: true };
+    const UNKNOWN: Typed = Typed { ty: Ty::Unknown, nullable: true };
+
+    fn known(value_type: ValueType, nullable: bool) -> Self {
+        Typed { ty: Ty::Known(value_type), nullable }
+    }
+
+    fn expr_type(self) -> Option<ExprType> {
+        match self.ty {
+            Ty::Known(value_type) => Some(ExprType { value_type, nullable: self.nullable }),
+            Ty::Null | Ty::Unknown => None,
+        }
+    }
+
+    /// Type of an expression which may produce either of two values, as the
+    /// branches of a CASE
+    fn either(self, other: Typed) -> Typed {
+        let ty = match (self.ty, other.ty) {
+            (Ty::Null, ty) | (ty, Ty::Null) => ty,
+            (Ty::Known(a), Ty::Known(b)) if a == b => Ty::Known(a),
+            _ => Ty::Unknown,
+        };
+
+        Typed { ty, nullable: self.nullable || other.nullable }
+    }
+
+    /// Type of an arithmetic operation on two operands
+    fn arithmetic(self, other: Typed) -> Typed {
+        use ValueType::{Integer, Real};
+
+        let ty = match (self.ty, other.ty) {
+            (Ty::Null, _) | (_, Ty::Null) => Ty::Null,
+            (Ty::Known(Integer), Ty::Known(Integer)) => Ty::Known(Integer),
+            (Ty::Known(Integer | Real), Ty::Known(Integer | Real)) => Ty::Known(Real),
+            _ => Ty::Unknown,
+        };
+
+        Typed { ty, nullable: self.nullable || other.nullable }
+    }
+}
+
+// binding power of binary operators, following SQLite's operator precedence
+const OR: u8 = 1;
+const AND: u8 = 2;
+const NOT: u8 = 3;
+const EQUALITY: u8 = 4;
+const COMPARISON: u8 = 5;
+const BITWISE: u8 = 6;
+const ADDITIVE: u8 = 7;
+const MULTIPLICATIVE: u8 = 8;
+const CONCAT: u8 = 9;
+const COLLATE: u8 = 10;
+const UNARY: u8 = 11;
+
+struct Parser<'t, 'a, 's> {
+    conn: &'s Connection,
+    scope: &'s [TableRef],
+    tokens: &'t [Token<'a>],
+    pos: usize,
+}
+
+impl<'t, 'a, 's> Parser<'t, 'a, 's> {
+    fn peek(&self) -> Option<&'t Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&'t Token<'a>> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn next(&mut self) -> Option<&'t Token<'a>> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().is_some_and(|t| t.is_keyword(keyword)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn punct(&mut self, punct: &str) -> bool {
+        if self.peek().is_some_and(|t| t.is_punct(punct)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the expression has been parsed in full, leaving at most an
+    /// alias for the result column
+    fn at_alias(&self) -> bool {
+        match &self.tokens[self.pos..] {
+            [] => true,
+            [alias] => alias.ident().is_some() || alias.kind == TokenKind::String,
+            [as_, alias] => as_.is_keyword("as") && (alias.ident().is_some() || alias.kind == TokenKind::String),
+            _ => false,
+        }
+    }
+
+    /// Skips from an opening paren past its matching closing paren
+    fn skip_parens(&mut self) -> Option<()> {
+        if !self.punct("(") {
+            return None;
+        }
+
+        let mut depth = 1usize;
+
+        while depth > 0 {
+            let token = self.next()?;
+
+            if token.is_punct("(") {
+                depth += 1;
+            } else if token.is_punct(")") {
+                depth -= 1;
+            }
+        }
+
+        Some(())
+    }
+
+    fn expr(&mut self, min_power: u8) -> Option<Typed> {
+        let mut lhs = self.prefix()?;
+
+        while let Some(token) = self.peek() {
+            let negated = token.is_keyword("not")
+                && self.peek_at(1).is_some_and(|t| {
+                    ["in", "like", "glob", "regexp", "match", "between", "null"].iter().any(|kw| t.is_keyword(kw))
+                });
+
+            let op = if negated { self.peek_at(1)? } else { token };
+
+            let power = binding_power(op);
+
+            if power == 0 || power <= min_power {
+                break;
+            }
+
+            self.pos += if negated { 2 } else { 1 };
+
+            lhs = self.infix(lhs, op, power)?;
+        }
+
+        Some(lhs)
+    }
+
+    fn infix(&mut self, lhs: Typed, op: &Token, power: u8) -> Option<Typed> {
+        let boolean = |nullable| Typed::known(ValueType::Integer, nullable);
+
+        if op.is_keyword("isnull") || op.is_keyword("notnull") || op.is_keyword("null") {
+            return Some(boolean(false));
+        }
+
+        if op.is_keyword("is") {
+            self.keyword("not");
+            if self.keyword("distinct") && !self.keyword("from") {
+                return None;
+            }
+
+            self.expr(power)?;
+            return Some(boolean(false));
+        }
+
+        if op.is_keyword("in") {
+            // NULL when the operand is NULL, or when it isn't found and the
+            // list holds a NULL, as in 1 IN (2, NULL)
+            let list_nullable = if !self.peek().is_some_and(|t| t.is_punct("(")) {
+                // IN table or table valued function
+                self.column_or_call()?;
+                true
+            } else if self.peek_at(1).is_some_and(|t| t.is_keyword("select") || t.is_keyword("with")) {
+                self.skip_parens()?;
+                true
+            } else {
+                self.pos += 1;
+                let mut nullable = false;
+
+                while !self.punct(")") {
+                    nullable |= self.expr(0)?.nullable;
+
+                    if !self.punct(",") && !self.peek()?.is_punct(")") {
+                        return None;
+                    }
+                }
+
+                nullable
+            };
+
+            return Some(boolean(lhs.nullable || list_nullable));
+        }
+
+        if op.is_keyword("between") {
+            let low = self.expr(power)?;
+            if !self.keyword("and") {
+                return None;
+            }
+            let high = self.expr(power)?;
+            return Some(boolean(lhs.nullable || low.nullable || high.nullable));
+        }
+
+        if op.is_keyword("collate") {
+            self.next()?.ident()?;
+            return Some(lhs);
+        }
+
+        let rhs = self.expr(power)?;
+        let nullable = lhs.nullable || rhs.nullable;
+
+        if ["like", "glob", "regexp", "match"].iter().any(|kw| op.is_keyword(kw)) {
+            if self.keyword("escape") {
+                self.expr(power)?;
+            }
+            return Some(boolean(nullable));
+        }
+
+        let typed = match op.text {
+            "||" => Typed::known(ValueType::Text, nullable),
+            "->" => Typed::known(ValueType::Text, true),
+            "->>" => Typed::UNKNOWN,
+            "+" | "-" | "*" => lhs.arithmetic(rhs),
+            // division by zero is NULL
+            "/" | "%" => Typed { nullable: true, ..lhs.arithmetic(rhs) },
+            "&" | "|" | "<<" | ">>" => Typed::known(ValueType::Integer, nullable),
+            _ => boolean(nullable),
+        };
+
+        Some(typed)
+    }
+
+    fn prefix(&mut self) -> Option<Typed> {
+        let token = self.peek()?;
+
+        match token.kind {
+            TokenKind::Number => {
+                self.pos += 1;
+                let real = !token.text.starts_with("0x") && !token.text.starts_with("0X")
+                    && token.text.contains(['.', 'e', 'E']);
+                let value_type = if real { ValueType::Real } else { ValueType::Integer };
+                return Some(Typed::known(value_type, false));
+            }
+            TokenKind::String => {
+                self.pos += 1;
+                return Some(Typed::known(ValueType::Text, false));
+            }
+            TokenKind::Blob => {
+                self.pos += 1;
+                return Some(Typed::known(ValueType::Blob, false));
+            }
+            TokenKind::Param => {
+                self.pos += 1;
+                return Some(Typed::UNKNOWN);
+            }
+            TokenKind::Punct => {
+                self.pos += 1;
+                return match token.text {
+                    "(" if self.peek().is_some_and(|t| t.is_keyword("select") || t.is_keyword("with")) => {
+                        // scalar subquery
+                        self.pos -= 1;
+                        self.skip_parens()?;
+                        Some(Typed::UNKNOWN)
+                    }
+                    "(" => {
+                        let inner = self.expr(0)?;
+                        // row values aren't allowed as result columns
+                        self.punct(")").then_some(inner)
+                    }
+                    "+" => self.expr(UNARY),
+                    "-" => {
+                        // negating anything but a number converts it to one,
+                        // as in -'a' which is the integer 0
+                        let operand = self.expr(UNARY)?;
+                        match operand.ty {
+                            Ty::Known(ValueType::Integer | ValueType::Real) | Ty::Null => Some(operand),
+                            _ => Some(Typed { ty: Ty::Unknown, nullable: operand.nullable }),
+                        }
+                    }
+                    "~" => {
+                        let operand = self.expr(UNARY)?;
+                        Some(Typed::known(ValueType::Integer, operand.nullable))
+                    }
+                    _ => None,
+                };
+            }
+            TokenKind::Ident | TokenKind::QuotedIdent => {}
+        }
+
+        if token.kind == TokenKind::Ident {
+            let keyword = token.text.to_ascii_lowercase();
+
+            match keyword.as_str() {
+                "null" => {
+                    self.pos += 1;
+                    return Some(Typed::NULL);
+                }
+                "true" | "false" => {
+                    self.pos += 1;
+                    return Some(Typed::known(ValueType::Integer, false));
+                }
+                "current_date" | "current_time" | "current_timestamp" => {
+                    self.pos += 1;
+                    return Some(Typed::known(ValueType::Text, false));
+                }
+                "not" => {
+                    self.pos += 1;
+                    let operand = self.expr(NOT)?;
+                    return Some(Typed::known(ValueType::Integer, operand.nullable));
+                }
+                "exists" => {
+                    self.pos += 1;
+                    self.skip_parens()?;
+                    return Some(Typed::known(ValueType::Integer, false));
+                }
+                "case" => {
+                    self.pos += 1;
+                    return self.case();
+                }
+                "cast" => {
+                    self.pos += 1;
+                    return self.cast();
+                }
+                _ => {}
+            }
+        }
+
+        self.column_or_call()
+    }
+
+    fn case(&mut self) -> Option<Typed> {
+        if !self.peek().is_some_and(|t| t.is_keyword("when")) {
+            // CASE base WHEN ...
+            self.expr(0)?;
+        }
+
+        let mut result: Option<Typed> = None;
+
+        while self.keyword("when") {
+            self.expr(0)?;
+
+            if !self.keyword("then") {
+                return None;
+            }
+
+            let branch = self.expr(0)?;
+            result = Some(result.map_or(branch, |result| result.either(branch)));
+        }
+
+        let mut result = result?;
+
+        if self.keyword("else") {
+            result = result.either(self.expr(0)?);
+        } else {
+            result.nullable = true;
+        }
+
+        self.keyword("end").then_some(result)
+    }
+
+    fn cast(&mut self) -> Option<Typed> {
+        if !self.punct("(") {
+            return None;
+        }
+
+        let operand = self.expr(0)?;
+
+        if !self.keyword("as") {
+            return None;
+        }
+
+        let mut type_name = String::new();
+
+        while let Some(token) = self.next() {
+            if token.is_punct(")") {
+                // the result takes the affinity of the type name
+                let ty = match declared_type(&type_name) {
+                    Some(value_type) => Ty::Known(value_type),
+                    None => Ty::Unknown,
+                };
+
+                return Some(Typed { ty, nullable: operand.nullable });
+            }
+
+            type_name.push_str(token.text);
+            type_name.push(' ');
+        }
+
+        None
+    }
+
+    fn column_or_call(&mut self) -> Option<Typed> {
+        let start = self.pos;
+
+        self.next()?.ident()?;
+
+        if self.peek().is_some_and(|t| t.is_punct("(")) {
+            let name = self.tokens[start].text.to_ascii_lowercase();
+            return self.call(&name);
+        }
+
+        // qualified column reference
+        while self.peek().is_some_and(|t| t.is_punct(".")) && self.pos < start + 5 {
+            self.pos += 2;
+        }
+
+        let column = column_ref(self.tokens.get(start..self.pos)?)?;
+
+        let Some((table, origin)) = column.resolve_table(self.conn, self.scope) else {
+            return Some(Typed::UNKNOWN);
+        };
+
+        let Some((decl_type, not_null)) = scope::column_type(self.conn, &origin) else {
+            return Some(Typed::UNKNOWN);
+        };
+
+        let ty = match declared_type(&decl_type) {
+            Some(value_type) => Ty::Known(value_type),
+            None => Ty::Unknown,
+        };
+
+        Some(Typed { ty, nullable: !not_null || table.optional })
+    }
+
+    fn call(&mut self, name: &str) -> Option<Typed> {
+        self.punct("(");
+
+        let mut args = Vec::new();
+
+        if self.punct("*") {
+            // count(*)
+        } else if !self.peek().is_some_and(|t| t.is_punct(")")) {
+            self.keyword("distinct");
+
+            loop {
+                args.push(self.expr(0)?);
+
+                if !self.punct(",") {
+                    break;
+                }
+            }
+
+            // ORDER BY within aggregates, as in group_concat(x ORDER BY y)
+            if self.keyword("order") {
+                while !self.peek()?.is_punct(")") {
+                    if self.peek()?.is_punct("(") {
+                        self.skip_parens()?;
+                    } else {
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+
+        if !self.punct(")") {
+            return None;
+        }
+
+        if self.keyword("filter") {
+            self.skip_parens()?;
+        }
+
+        if self.keyword("over") {
+            if self.peek()?.is_punct("(") {
+                self.skip_parens()?;
+            } else {
+                self.next()?.ident()?;
+            }
+        }
+
+        Some(function_type(name, &args))
+    }
+}
+
+fn binding_power(op: &Token) -> u8 {
+    match op.kind {
+        TokenKind::Punct => match op.text {
+            "=" | "==" | "!=" | "<>" => EQUALITY,
+            "<" | "<=" | ">" | ">=" => COMPARISON,
+            "&" | "|" | "<<" | ">>" => BITWISE,
+            "+" | "-" => ADDITIVE,
+            "*" | "/" | "%" => MULTIPLICATIVE,
+            "||" | "->" | "->>" => CONCAT,
+            _ => 0,
+        },
+        TokenKind::Ident => match op.text.to_ascii_lowercase().as_str() {
+            "or" => OR,
+            "and" => AND,
+            "is" | "in" | "like" | "glob" | "regexp" | "match" | "between"
+                | "isnull" | "notnull" | "null" => EQUALITY,
+            "collate" => COLLATE,
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+/// Maps a column's declared type to the type of its values. Columns with
+/// NUMERIC affinity may hold either integers or reals.
+fn declared_type(decl_type: &str) -> Option<ValueType> {
+    match Affinity::of(decl_type) {
+        Affinity::Integer => Some(ValueType::Integer),
+        Affinity::Real => Some(ValueType::Real),
+        Affinity::Text => Some(ValueType::Text),
+        Affinity::Blob => Some(ValueType::Blob),
+        Affinity::Numeric => None,
+    }
+}
+
+fn function_type(name: &str, args: &[Typed]) -> Typed {
+    use ValueType::{Blob, Integer, Real, Text};
+
+    let any_nullable = args.iter().any(|arg| arg.nullable);
+    let first = args.first().copied().unwrap_or(Typed::UNKNOWN);
+
+    // numeric functions of one argument returning a value of the same type
+    let numeric = |nullable: bool| match first.ty {
+        Ty::Known(Integer | Real) => Typed { ty: first.ty, nullable },
+        _ => Typed::UNKNOWN,
+    };
+
+    match name {
+        "count" => Typed::known(Integer, false),
+        "total" => Typed::known(Real, false),
+        "avg" => Typed::known(Real, true),
+        // aggregates over no rows are NULL
+        "sum" => numeric(true),
+        "min" | "max" if args.len() == 1 => Typed { nullable: true, ..first },
+        "min" | "max" => args.iter()
+            .copied()
+            .reduce(Typed::either)
+            .unwrap_or(Typed::UNKNOWN),
+        "coalesce" | "ifnull" => {
+            let nullable = args.iter().all(|arg| arg.nullable);
+            let typed = args.iter()
+                .copied()
+                .reduce(Typed::either)
+                .unwrap_or(Typed::UNKNOWN);
+            Typed { nullable, ..typed }
+        }
+        "nullif" => Typed { nullable: true, ..first },
+        "iif" if args.len() == 3 => args[1].either(args[2]),
+        "abs" => numeric(first.nullable),
+        // the math functions return NULL for arguments outside their domain
+        // or which aren't numbers, as in sqrt(-1) or ceil('x'), so whatever
+        // their arguments they may be NULL
+        "ceil" | "ceiling" | "floor" | "trunc" => numeric(true),
+        "likely" | "unlikely" | "likelihood" => first,
+        "lag" | "lead" | "first_value" | "last_value" | "nth_value" => Typed { nullable: true, ..first },
+
+        "random" | "changes" | "total_changes" | "last_insert_rowid"
+            | "row_number" | "rank" | "dense_rank" | "ntile" => Typed::known(Integer, false),
+        "length" | "octet_length" | "instr" | "glob" | "like" => Typed::known(Integer, any_nullable),
+        // NULL for non-numbers and empty strings respectively
+        "sign" | "unicode" => Typed::known(Integer, true),
+        "unixepoch" => Typed::known(Integer, true),
+
+        "percent_rank" | "cume_dist" | "pi" => Typed::known(Real, false),
+        "round" => Typed::known(Real, any_nullable),
+        "sqrt" | "exp" | "ln" | "log" | "log10" | "log2" | "pow" | "power"
+            | "sin" | "cos" | "tan" | "asin" | "acos" | "atan" | "atan2"
+            | "sinh" | "cosh" | "tanh" | "asinh" | "acosh" | "atanh"
+            | "degrees" | "radians" | "mod" => Typed::known(Real, true),
+        "julianday" => Typed::known(Real, true),
+
+        "typeof" | "quote" | "concat" | "concat_ws" | "char"
+            | "sqlite_version" | "json_quote" | "json_array" | "json_object"
+            | "hex" => Typed::known(Text, false),
+        // NULL when the format string is NULL or empty
+        "printf" | "format" => Typed::known(Text, true),
+        "date" | "time" | "datetime" | "strftime" | "timediff"
+            | "group_concat" | "string_agg" | "json_group_array" | "json_group_object"
+            => Typed::known(Text, true),
+        // substrings of blobs are blobs
+        "substr" | "substring" if first.ty == Ty::Known(Blob) => Typed::known(Blob, any_nullable),
+        "lower" | "upper" | "trim" | "ltrim" | "rtrim" | "replace" | "substr" | "substring"
+            | "soundex" | "unistr" | "json" | "json_extract_string" | "json_type"
+            | "json_insert" | "json_replace" | "json_set" | "json_remove" | "json_patch"
+            => Typed::known(Text, any_nullable),
+
+        "zeroblob" | "randomblob" => Typed::known(Blob, false),
+        "unhex" => Typed::known(Blob, true),
+
+        _ => Typed::UNKNOWN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    use ValueType::{Blob, Integer, Real, Text};
+
+    fn conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("
+            CREATE TABLE t (i INTEGER NOT NULL, n INTEGER, r REAL NOT NULL, s TEXT NOT NULL, b BLOB);
+            CREATE TABLE u (id INTEGER NOT NULL, t_i INTEGER NOT NULL);
+        ").unwrap();
+        conn
+    }
+
+    /// Type inferred for the single result column of `SELECT <expr> FROM t`
+    fn infer(conn: &Connection, expr: &str) -> Option<ExprType> {
+        let sql = format!("SELECT {expr} FROM t");
+        let mut columns = result_columns(conn, &tokenize(&sql), 1);
+        columns.pop().unwrap().expr_type
+    }
+
+    fn known(value_type: ValueType, nullable: bool) -> Option<ExprType> {
+        Some(ExprType { value_type, nullable })
+    }
+
+    #[test]
+    fn inference_table() {
+        let conn = conn();
+
+        let cases = [
+            // literals and columns
+            ("1", known(Integer, false)),
+            ("1.5", known(Real, false)),
+            ("'a'", known(Text, false)),
+            ("x'00'", known(Blob, false)),
+            ("NULL", None),
+            // operators
+            ("i + 1", known(Integer, false)),
+            ("i * r", known(Real, false)),
+            ("n + 1", known(Integer, true)),
+            ("s || 'x'", known(Text, false)),
+            ("i = 1", known(Integer, false)),
+            ("n IS NULL", known(Integer, false)),
+            ("-i", known(Integer, false)),
+            ("-n", known(Integer, true)),
+            ("+s", known(Text, false)),
+            ("i IN (1, 2)", known(Integer, false)),
+            ("i IN (1, n)", known(Integer, true)),
+            ("i IN (1, NULL)", known(Integer, true)),
+            ("n NOT IN (1, 2)", known(Integer, true)),
+            ("i IN (SELECT id FROM u)", known(Integer, true)),
+            ("CAST(s AS INTEGER)", known(Integer, false)),
+            ("CASE WHEN i THEN 'a' ELSE 'b' END", known(Text, false)),
+            ("CASE WHEN i THEN 'a' END", known(Text, true)),
+            // aggregates
+            ("count(*)", known(Integer, false)),
+            ("total(i)", known(Real, false)),
+            ("sum(i)", known(Integer, true)),
+            ("avg(i)", known(Real, true)),
+            ("max(i)", known(Integer, true)),
+            ("max(i, 2)", known(Integer, false)),
+            ("group_concat(s)", known(Text, true)),
+            // scalar functions
+            ("coalesce(n, 0)", known(Integer, false)),
+            ("coalesce(n, NULL)", known(Integer, true)),
+            ("ifnull(n, i)", known(Integer, false)),
+            ("nullif(i, 0)", known(Integer, true)),
+            ("iif(i, 'a', 'b')", known(Text, false)),
+            ("abs(i)", known(Integer, false)),
+            ("abs(n)", known(Integer, true)),
+            ("round(r)", known(Real, false)),
+            ("length(s)", known(Integer, false)),
+            ("length(b)", known(Integer, true)),
+            ("lower(s)", known(Text, false)),
+            ("substr(s, 1, n)", known(Text, true)),
+            ("substr(b, 1, 2)", known(Blob, true)),
+            ("printf('%d', i)", known(Text, true)),
+            ("format(s)", known(Text, true)),
+            ("hex(b)", known(Text, false)),
+            ("typeof(n)", known(Text, false)),
+            ("datetime('now')", known(Text, true)),
+            ("randomblob(4)", known(Blob, false)),
+            ("pi()", known(Real, false)),
+            // NULL for arguments outside their domain or which aren't numbers
+            ("sqrt(r)", known(Real, true)),
+            ("ln(r)", known(Real, true)),
+            ("log(r)", known(Real, true)),
+            ("log10(i)", known(Real, true)),
+            ("log2(i)", known(Real, true)),
+            ("acos(r)", known(Real, true)),
+            ("asin(r)", known(Real, true)),
+            ("pow(r, 0.5)", known(Real, true)),
+            ("power(i, i)", known(Real, true)),
+            ("mod(i, i)", known(Real, true)),
+            ("sin(s)", known(Real, true)),
+            ("ceil(i)", known(Integer, true)),
+            ("floor(r)", known(Real, true)),
+            ("sign(i)", known(Integer, true)),
+            ("unicode(s)", known(Integer, true)),
+            // not inferrable
+            ("unknown_function(i)", None),
+            ("i + s", None),
+            ("-s", None),
+            ("CASE WHEN i THEN 1 ELSE 'a' END", None),
+        ];
+
+        for (expr, expected) in cases {
+            assert_eq!(infer(&conn, expr), expected, "{expr}");
+        }
+    }
+
+    #[test]
+    fn functions_inferred_not_null_are_never_null() {
+        let conn = conn();
+
+        // arguments chosen to make SQLite return NULL where it can
+        conn.execute("INSERT INTO t VALUES (0, NULL, -1.5, '', NULL)", []).unwrap();
+
+        let exprs = [
+            "abs(i)", "round(r)", "length(s)", "lower(s)", "hex(b)", "typeof(n)", "total(i)",
+            "count(n)", "coalesce(n, 0)", "sqrt(r)", "ln(i)", "log(r)", "acos(r)", "asin(r)",
+            "pow(r, 0.5)", "mod(i, i)", "ceil(s)", "floor(s)", "trunc(s)", "sign(s)", "unicode(s)",
+            "printf(s, n)", "i IN (1, n)", "i IN (1, NULL)", "i IN (SELECT n FROM t)",
+        ];
+
+        for expr in exprs {
+            // expressions which aren't inferred must be annotated instead
+            let Some(expr_type) = infer(&conn, expr) else {
+                continue;
+            };
+
+            let is_null: bool = conn
+                .query_row(&format!("SELECT {expr} IS NULL FROM t"), [], |row| row.get(0))
+                .unwrap();
+
+            assert!(!is_null || expr_type.nullable, "{expr} is NULL but inferred NOT NULL");
+        }
+    }
+
+    #[test]
+    fn columns_of_outer_joins() {
+        let conn = conn();
+        let sql = "SELECT t.i, u.id, i + 1 AS x FROM t LEFT JOIN u ON u.t_i = t.i";
+        let columns = result_columns(&conn, &tokenize(sql), 3);
+
+        assert!(!columns[0].outer_joined);
+        assert!(columns[1].outer_joined);
+        assert_eq!(columns[1].origin.as_ref().map(|origin| origin.table.as_str()), Some("u"));
+        assert_eq!(columns[2].expr_type, known(Integer, false));
+    }
+
+    #[test]
+    fn columns_of_outer_joined_subqueries() {
+        let conn = conn();
+        let sql = "SELECT t.i, x.id, t_i, x.renamed, x.* FROM t LEFT JOIN (SELECT id, t_i, id AS renamed FROM u) AS x ON x.t_i = t.i";
+        let columns = result_columns(&conn, &tokenize(sql), 7);
+
+        let outer_joined = columns.iter().map(|column| column.outer_joined).collect::<Vec<_>>();
+        assert_eq!(outer_joined, [false, true, true, true, true, true, true]);
+    }
+}
@@ -0,0 +1,280 @@
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Bare word, either an identifier or a keyword
+    Ident,
+    /// Identifier quoted with "", `` or []
+    QuotedIdent,
+    String,
+    Number,
+    Blob,
+    /// One of ?, ?NNN, :name, @name, $name
+    Param,
+    Punct,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Byte offset of the token in the SQL text
+    pub start: usize,
+}
+
+impl<'a> Token<'a> {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Ident && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    pub fn is_punct(&self, punct: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == punct
+    }
+
+    /// Returns the identifier this token names with any quoting removed, or
+    /// None if the token is not an identifier
+    pub fn ident(&self) -> Option<Cow<'a, str>> {
+        match self.kind {
+            TokenKind::Ident => Some(Cow::Borrowed(self.text)),
+            TokenKind::QuotedIdent => Some(unquote(self.text)),
+            _ => None,
+        }
+    }
+}
+
+fn unquote(text: &str) -> Cow<'_, str> {
+    let inner = &text[1..text.len() - 1];
+
+    match text.as_bytes()[0] {
+        b'"' => Cow::Owned(inner.replace("\"\"", "\"")),
+        b'`' => Cow::Owned(inner.replace("``", "`")),
+        _ => Cow::Borrowed(inner),
+    }
+}
+
+/// Splits SQL text into tokens following SQLite's lexical rules, skipping
+/// whitespace and comments. Unterminated quotes and comments run to the end
+/// of the input, malformed input is left for SQLite itself to reject.
+pub fn tokenize(sql: &str) -> Vec<Token<'_>> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let start = pos;
+        let c = bytes[pos];
+
+        let kind = match c {
+            _ if c.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            b'-' if bytes.get(pos + 1) == Some(&b'-') => {
+                pos = find_from(bytes, pos, b"\n").map(|p| p + 1).unwrap_or(bytes.len());
+                continue;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = find_from(bytes, pos + 2, b"*/").map(|p| p + 2).unwrap_or(bytes.len());
+                continue;
+            }
+            b'\'' => {
+                pos = end_of_quoted(bytes, pos, b'\'');
+                TokenKind::String
+            }
+            b'"' | b'`' => {
+                pos = end_of_quoted(bytes, pos, c);
+                TokenKind::QuotedIdent
+            }
+            b'[' => {
+                pos = find_from(bytes, pos, b"]").map(|p| p + 1).unwrap_or(bytes.len());
+                TokenKind::QuotedIdent
+            }
+            b'x' | b'X' if bytes.get(pos + 1) == Some(&b'\'') => {
+                pos = end_of_quoted(bytes, pos + 1, b'\'');
+                TokenKind::Blob
+            }
+            b'0'..=b'9' => {
+                pos = end_of_number(bytes, pos);
+                TokenKind::Number
+            }
+            b'.' if bytes.get(pos + 1).is_some_and(u8::is_ascii_digit) => {
+                pos = end_of_number(bytes, pos);
+                TokenKind::Number
+            }
+            b'?' => {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+                TokenKind::Param
+            }
+            b':' | b'@' | b'$' if bytes.get(pos + 1).is_some_and(|c| is_ident_char(*c)) => {
+                pos += 1;
+                while pos < bytes.len() && is_ident_char(bytes[pos]) {
+                    pos += 1;
+                }
+                TokenKind::Param
+            }
+            _ if is_ident_start(c) => {
+                while pos < bytes.len() && is_ident_char(bytes[pos]) {
+                    pos += 1;
+                }
+                TokenKind::Ident
+            }
+            _ => {
+                pos += punct_len(&bytes[pos..]);
+                TokenKind::Punct
+            }
+        };
+
+        tokens.push(Token { kind, text: &sql[start..pos], start });
+    }
+
+    tokens
+}
+
+fn find_from(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes[from..]
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|p| p + from)
+}
+
+fn end_of_quoted(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut pos = start + 1;
+
+    while pos < bytes.len() {
+        if bytes[pos] == quote {
+            // doubled quote is an escaped quote
+            if bytes.get(pos + 1) == Some(&quote) {
+                pos += 2;
+                continue;
+            }
+
+            return pos + 1;
+        }
+
+        pos += 1;
+    }
+
+    bytes.len()
+}
+
+fn end_of_number(bytes: &[u8], start: usize) -> usize {
+    let mut pos = start;
+
+    if bytes[pos] == b'0' && matches!(bytes.get(pos + 1), Some(b'x' | b'X')) {
+        pos += 2;
+        while pos < bytes.len() && (bytes[pos].is_ascii_hexdigit() || bytes[pos] == b'_') {
+            pos += 1;
+        }
+        return pos;
+    }
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'0'..=b'9' | b'.' | b'_' => pos += 1,
+            b'e' | b'E' => {
+                pos += 1;
+                if matches!(bytes.get(pos), Some(b'+' | b'-')) {
+                    pos += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    pos
+}
+
+fn is_ident_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c >= 0x80
+}
+
+fn is_ident_char(c: u8) -> bool {
+    is_ident_start(c) || c.is_ascii_digit() || c == b'$'
+}
+
+fn punct_len(bytes: &[u8]) -> usize {
+    const MULTI: &[&[u8]] = &[b"->>", b"->", b"||", b"<=", b">=", b"==", b"!=", b"<>", b"<<", b">>"];
+
+    MULTI.iter()
+        .find(|op| bytes.starts_with(op))
+        .map(|op| op.len())
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(sql: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(sql).into_iter().map(|t| (t.kind, t.text)).collect()
+    }
+
+    #[test]
+    fn skips_whitespace_and_comments() {
+        assert_eq!(
+            kinds("SELECT -- comment\n a /* block */ FROM t"),
+            [(TokenKind::Ident, "SELECT"), (TokenKind::Ident, "a"), (TokenKind::Ident, "FROM"), (TokenKind::Ident, "t")],
+        );
+    }
+
+    #[test]
+    fn params() {
+        assert_eq!(
+            kinds("? ?12 :name @name $name"),
+            [(TokenKind::Param, "?"), (TokenKind::Param, "?12"), (TokenKind::Param, ":name"), (TokenKind::Param, "@name"), (TokenKind::Param, "$name")],
+        );
+    }
+
+    #[test]
+    fn quoted_identifiers() {
+        let tokens = tokenize(r#""a ""b""" `c``d` [e f]"#);
+
+        assert!(tokens.iter().all(|t| t.kind == TokenKind::QuotedIdent));
+        assert_eq!(tokens.iter().map(|t| t.ident().unwrap()).collect::<Vec<_>>(), [r#"a "b""#, "c`d", "e f"]);
+    }
+
+    #[test]
+    fn strings_and_blobs() {
+        assert_eq!(
+            kinds("'it''s' x'00ff' X'01'"),
+            [(TokenKind::String, "'it''s'"), (TokenKind::Blob, "x'00ff'"), (TokenKind::Blob, "X'01'")],
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            kinds("1 2.5 .5 1e10 1.5E-3 0x1F 1_000"),
+            ["1", "2.5", ".5", "1e10", "1.5E-3", "0x1F", "1_000"].map(|n| (TokenKind::Number, n)),
+        );
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(
+            kinds("a->>'$.x'||b<>c<=d"),
+            [
+                (TokenKind::Ident, "a"), (TokenKind::Punct, "->>"), (TokenKind::String, "'$.x'"),
+                (TokenKind::Punct, "||"), (TokenKind::Ident, "b"), (TokenKind::Punct, "<>"),
+                (TokenKind::Ident, "c"), (TokenKind::Punct, "<="), (TokenKind::Ident, "d"),
+            ],
+        );
+    }
+
+    #[test]
+    fn unterminated_input_runs_to_the_end() {
+        assert_eq!(kinds("'abc"), [(TokenKind::String, "'abc")]);
+        assert_eq!(kinds("a /* abc"), [(TokenKind::Ident, "a")]);
+    }
+
+    #[test]
+    fn keywords_are_case_insensitive() {
+        let tokens = tokenize("select \"select\"");
+
+        assert!(tokens[0].is_keyword("SELECT"));
+        assert!(!tokens[1].is_keyword("select"));
+    }
+}
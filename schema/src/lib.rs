@@ -1,5 +1,18 @@
 mod statement;
+mod affinity;
+mod annotation;
 mod ffi;
+mod infer;
+mod lexer;
+mod migrations;
+mod params;
+mod returning;
 mod schema;
+mod scope;
 
-pub use schema::{Schema, LoadError, TableColumn, QueryInfo, ResultColumn};
+pub use affinity::Affinity;
+pub use annotation::{Annotation, Nullability};
+pub use infer::{ExprType, ValueType};
+pub use params::ParamType;
+pub use schema::{Schema, SqlError, LoadError, TableColumn, QueryInfo, ResultColumn, QueryParam};
+pub use scope::ColumnOrigin;
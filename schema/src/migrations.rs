@@ -0,0 +1,286 @@
+use std::ffi::CString;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{ffi, Connection, OpenFlags};
+
+use crate::lexer;
+use crate::schema::LoadError;
+
+/// Applies the schema at `path`, either a single SQL file, a directory of
+/// migrations or an SQLite database
+pub fn apply_path(conn: &Connection, path: &Path) -> Result<(), LoadError> {
+    if path.is_dir() {
+        for migration in migration_files(path)? {
+            apply_file(conn, &migration)?;
+        }
+
+        Ok(())
+    } else if is_database(path)? {
+        copy_database_schema(conn, path)
+    } else {
+        apply_file(conn, path)
+    }
+}
+
+fn is_database(path: &Path) -> Result<bool, LoadError> {
+    let mut header = [0; 16];
+
+    let read = std::fs::File::open(path)
+        .and_then(|mut file| file.read(&mut header))
+        .map_err(LoadError::Io)?;
+
+    Ok(read == header.len() && &header == b"SQLite format 3\0")
+}
+
+/// Recreates the schema of an existing database by executing the SQL it
+/// stores for each of its tables, views, indexes and triggers. The database
+/// is only read, and its rows are left behind.
+///
+/// The shadow tables of virtual tables are skipped, as creating the virtual
+/// table creates them again.
+fn copy_database_schema(conn: &Connection, path: &Path) -> Result<(), LoadError> {
+    let database_error = |error| LoadError::Database { path: path.to_owned(), error };
+
+    let source = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(database_error)?;
+
+    // tables first, as the other objects refer to them
+    let statements = source
+        .prepare(&format!("
+            SELECT sql FROM sqlite_schema AS object
+            WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%' AND {}
+            ORDER BY type <> 'table', rowid
+        ", not_shadow_table(rusqlite::version_number())))
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(database_error)?;
+
+    for sql in statements {
+        conn.execute_batch(&sql).map_err(database_error)?;
+    }
+
+    Ok(())
+}
+
+/// Condition on `object` in sqlite_schema excluding shadow tables. These are
+/// reported by table_list since SQLite 3.37, and before that are told apart
+/// by being named after a virtual table.
+fn not_shadow_table(sqlite_version: i32) -> &'static str {
+    if sqlite_version >= 3_037_000 {
+        "name NOT IN (SELECT name FROM pragma_table_list WHERE schema = 'main' AND type = 'shadow')"
+    } else {
+        "NOT EXISTS (
+            SELECT 1 FROM sqlite_schema AS vtab
+            WHERE vtab.sql LIKE 'CREATE VIRTUAL TABLE %'
+            AND substr(object.name, 1, length(vtab.name) + 1) = vtab.name || '_'
+        )"
+    }
+}
+
+/// Lists the migrations in a directory in version order, by the number their
+/// file names start with, as in `0001_init.sql`. Down migrations named
+/// `*.down.sql` are skipped.
+fn migration_files(dir: &Path) -> Result<Vec<PathBuf>, LoadError> {
+    let mut migrations = Vec::new();
+
+    for entry in std::fs::read_dir(dir).map_err(LoadError::Io)? {
+        let path = entry.map_err(LoadError::Io)?.path();
+
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        if !name.ends_with(".sql") || name.ends_with(".down.sql") {
+            continue;
+        }
+
+        let digits = name.find(|c: char| !c.is_ascii_digit()).unwrap_or(name.len());
+
+        let Ok(version) = name[..digits].parse::<u64>() else {
+            return Err(LoadError::MigrationName(path));
+        };
+
+        migrations.push((version, path));
+    }
+
+    migrations.sort();
+
+    Ok(migrations.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Executes a SQL file a statement at a time, so a failure can be reported
+/// with the line of the statement which caused it
+fn apply_file(conn: &Connection, path: &Path) -> Result<(), LoadError> {
+    let sql = std::fs::read_to_string(path).map_err(LoadError::Io)?;
+
+    for (start, statement) in statements(&sql) {
+        conn.execute_batch(statement).map_err(|error| LoadError::File {
+            path: path.to_owned(),
+            line: 1 + sql[..start].matches('\n').count(),
+            error,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Splits SQL into statements, with the byte offset each starts at.
+/// Semicolons only end a statement where SQLite considers it complete, so
+/// those within the body of a trigger don't.
+fn statements(sql: &str) -> Vec<(usize, &str)> {
+    let tokens = lexer::tokenize(sql);
+    let mut statements = Vec::new();
+    let mut start = None;
+
+    for token in &tokens {
+        let stmt_start = *start.get_or_insert(token.start);
+
+        if token.is_punct(";") {
+            let end = token.start + token.text.len();
+
+            if is_complete(&sql[stmt_start..end]) {
+                statements.push((stmt_start, &sql[stmt_start..end]));
+                start = None;
+            }
+        }
+    }
+
+    if let Some(start) = start {
+        statements.push((start, &sql[start..]));
+    }
+
+    statements
+}
+
+fn is_complete(sql: &str) -> bool {
+    let Ok(sql) = CString::new(sql) else {
+        return true;
+    };
+
+    unsafe { ffi::sqlite3_complete(sql.as_ptr()) != 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::Schema;
+
+    fn migrations(files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+
+        for (name, sql) in files {
+            fs::write(dir.path().join(name), sql).unwrap();
+        }
+
+        dir
+    }
+
+    fn file_names(paths: &[PathBuf]) -> Vec<&str> {
+        paths.iter().map(|path| path.file_name().unwrap().to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn migrations_are_ordered_by_version() {
+        let dir = migrations(&[
+            ("10_later.sql", ""),
+            ("0002_posts.sql", ""),
+            ("0001_init.sql", ""),
+            ("0002_posts.down.sql", ""),
+            ("README.md", ""),
+        ]);
+
+        let files = migration_files(dir.path()).unwrap();
+
+        assert_eq!(file_names(&files), ["0001_init.sql", "0002_posts.sql", "10_later.sql"]);
+    }
+
+    #[test]
+    fn migrations_must_start_with_a_version() {
+        let dir = migrations(&[("0001_init.sql", ""), ("init.sql", "")]);
+
+        let error = migration_files(dir.path()).unwrap_err();
+
+        assert!(matches!(error, LoadError::MigrationName(path) if path.ends_with("init.sql")));
+    }
+
+    #[test]
+    fn directories_apply_migrations_in_order() {
+        let dir = migrations(&[
+            ("0002_posts.sql", "ALTER TABLE users ADD COLUMN email TEXT;\nCREATE TABLE posts (id INTEGER, user_id INTEGER);"),
+            ("0001_init.sql", "CREATE TABLE users (id INTEGER, name TEXT);"),
+            ("0002_posts.down.sql", "DROP TABLE users;"),
+        ]);
+
+        let schema = Schema::from_file(dir.path()).unwrap();
+        let names = schema.columns("users").unwrap().into_iter().map(|col| col.name).collect::<Vec<_>>();
+
+        assert_eq!(names, ["id", "name", "email"]);
+        assert!(schema.tables().unwrap().contains(&"posts".to_owned()));
+    }
+
+    #[test]
+    fn failures_report_the_file_and_line() {
+        let dir = migrations(&[
+            ("0001_init.sql", "CREATE TABLE users (id INTEGER);"),
+            ("0002_bad.sql", "CREATE TABLE posts (id INTEGER);\n\n-- typo\nCREATE TABEL comments (id INTEGER);\n"),
+        ]);
+
+        let Err(LoadError::File { path, line, .. }) = Schema::from_file(dir.path()) else {
+            panic!("expected a migration to fail");
+        };
+
+        assert!(path.ends_with("0002_bad.sql"));
+        assert_eq!(line, 4);
+    }
+
+    #[test]
+    fn database_schemas_skip_shadow_tables() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fts.db");
+
+        Connection::open(&path).unwrap().execute_batch("
+            CREATE TABLE notes (id INTEGER NOT NULL PRIMARY KEY, body TEXT NOT NULL);
+            CREATE VIRTUAL TABLE notes_fts USING fts5(body, content = 'notes', content_rowid = 'id');
+            CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
+                INSERT INTO notes_fts (rowid, body) VALUES (new.id, new.body);
+            END;
+            INSERT INTO notes (body) VALUES ('hello');
+        ").unwrap();
+
+        let source = Connection::open(&path).unwrap();
+
+        for version in [3_037_000, 3_036_000] {
+            let objects = source
+                .prepare(&format!("SELECT name FROM sqlite_schema AS object WHERE {} ORDER BY name", not_shadow_table(version)))
+                .unwrap()
+                .query_map([], |row| row.get::<_, String>(0))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(objects, ["notes", "notes_ai", "notes_fts"], "SQLite {version}");
+        }
+
+        // the shadow tables are recreated with the virtual table
+        let schema = Schema::from_file(&path).unwrap();
+        assert!(schema.tables().unwrap().contains(&"notes_fts_data".to_owned()));
+
+        let info = schema.prepare("SELECT notes.id FROM notes JOIN notes_fts ON notes_fts.rowid = notes.id WHERE notes_fts MATCH ?").unwrap();
+        assert_eq!(info.columns().len(), 1);
+    }
+
+    #[test]
+    fn statements_split_outside_of_triggers() {
+        let sql = "CREATE TABLE t (a);\nCREATE TRIGGER tr AFTER INSERT ON t BEGIN\n  UPDATE t SET a = 1;\n  DELETE FROM t;\nEND;\nSELECT ';'";
+        let starts = statements(sql).into_iter().map(|(start, _)| start).collect::<Vec<_>>();
+
+        assert_eq!(starts, [0, sql.find("CREATE TRIGGER").unwrap(), sql.find("SELECT").unwrap()]);
+    }
+}
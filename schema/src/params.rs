@@ -0,0 +1,504 @@
+use rusqlite::Connection;
+
+use crate::lexer::{Token, TokenKind};
+use crate::scope::{self, column_ref, ColumnOrigin, ColumnRef, TableRef};
+
+/// What a parameter is known to stand for, from the context it appears in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamType {
+    /// Assigned to or inserted into a table column, or compared with one by
+    /// IS, so NULL when the column is nullable
+    Column(ColumnOrigin),
+    /// Compared with a table column by an operator which is never true for
+    /// NULL, as in `col = ?`, so NOT NULL whether or not the column is
+    Compared(ColumnOrigin),
+    /// Operand of LIMIT or OFFSET
+    Integer,
+    Unknown,
+}
+
+/// Works out the type of each of the `names.len()` parameters of a statement
+/// from where they appear in its SQL. `names` are SQLite's names for each
+/// parameter, in index order.
+pub fn param_types(conn: &Connection, tokens: &[Token], names: &[Option<String>]) -> Vec<ParamType> {
+    let scope = scope::table_refs(tokens);
+    let mut types = vec![ParamType::Unknown; names.len()];
+    let mut max_index = 0;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Param {
+            continue;
+        }
+
+        // anonymous parameters take the next index after the largest so far,
+        // as SQLite numbers them
+        let index = if token.text == "?" {
+            max_index + 1
+        } else {
+            match names.iter().position(|name| name.as_deref() == Some(token.text)) {
+                Some(pos) => pos + 1,
+                None => continue,
+            }
+        };
+
+        max_index = max_index.max(index);
+
+        let Some(slot) = types.get_mut(index - 1) else {
+            continue;
+        };
+
+        if !matches!(slot, ParamType::Unknown) {
+            continue;
+        }
+
+        *slot = param_type(conn, tokens, &scope, idx);
+    }
+
+    types
+}
+
+fn param_type(conn: &Connection, tokens: &[Token], scope: &[TableRef], idx: usize) -> ParamType {
+    if is_limit_operand(tokens, idx) {
+        return ParamType::Integer;
+    }
+
+    if let Some((column, comparison)) = compared_column(tokens, idx) {
+        if let Some(origin) = column.resolve(conn, scope) {
+            return match comparison {
+                Comparison::Value => ParamType::Compared(origin),
+                Comparison::NullSafe | Comparison::Assignment => ParamType::Column(origin),
+            };
+        }
+    }
+
+    if let Some(origin) = inserted_column(conn, tokens, scope, idx) {
+        return ParamType::Column(origin);
+    }
+
+    ParamType::Unknown
+}
+
+const COMPARISONS: &[&str] = &["=", "==", "!=", "<>", "<", "<=", ">", ">="];
+
+// operators binding tighter than comparisons, which make an adjacent column
+// reference part of a larger expression
+const ARITHMETIC: &[&str] = &["||", "->", "->>", "*", "/", "%", "+", "-", "&", "|", "<<", ">>", "~"];
+
+fn is_any_punct(token: Option<&Token>, puncts: &[&str]) -> bool {
+    token.is_some_and(|t| puncts.iter().any(|p| t.is_punct(p)))
+}
+
+fn is_keyword(token: Option<&Token>, keyword: &str) -> bool {
+    token.is_some_and(|t| t.is_keyword(keyword))
+}
+
+fn is_operand(token: Option<&Token>) -> bool {
+    matches!(token.map(|t| t.kind), Some(TokenKind::Param | TokenKind::String | TokenKind::Number))
+}
+
+fn get<'t, 'a>(tokens: &'t [Token<'a>], idx: usize, offset: isize) -> Option<&'t Token<'a>> {
+    tokens.get(idx.checked_add_signed(offset)?)
+}
+
+/// How a parameter is compared with a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    /// By =, <, IN, LIKE and so on, which are never true for NULL
+    Value,
+    /// By IS or IS NOT, which treat NULL as a value
+    NullSafe,
+    /// Not compared but assigned, as in `SET col = ?`
+    Assignment,
+}
+
+/// Finds the column a parameter is compared with, as in `col = ?`,
+/// `? <> tbl.col`, `col IN (?, ?)`, `col BETWEEN ? AND ?` or `col LIKE ?`
+fn compared_column(tokens: &[Token], idx: usize) -> Option<(ColumnRef, Comparison)> {
+    // operators with the column on the left
+    let mut before = idx.checked_sub(1)?;
+
+    // skip over earlier values of an IN list
+    if tokens[before].is_punct(",") {
+        while tokens[before].is_punct(",") && is_operand(get(tokens, before, -1)) {
+            before = before.checked_sub(2)?;
+        }
+
+        if !tokens[before].is_punct("(") || !is_keyword(get(tokens, before, -1), "in") {
+            return None;
+        }
+    }
+
+    // second operand of BETWEEN
+    if tokens[before].is_keyword("and")
+        && is_operand(get(tokens, before, -1))
+        && is_keyword(get(tokens, before, -2), "between")
+    {
+        before -= 2;
+    }
+
+    let op = &tokens[before];
+
+    let left = if is_any_punct(Some(op), COMPARISONS) {
+        Some((before, Comparison::Value))
+    } else if op.is_punct("(") && is_keyword(get(tokens, before, -1), "in") {
+        Some((before - 1, Comparison::Value))
+    } else if op.is_keyword("is") {
+        Some((before, Comparison::NullSafe))
+    } else if ["like", "glob", "between"].iter().any(|kw| op.is_keyword(kw)) {
+        Some((before, Comparison::Value))
+    } else if op.is_keyword("not") && is_keyword(get(tokens, before, -1), "is") {
+        Some((before - 1, Comparison::NullSafe))
+    } else {
+        None
+    };
+
+    if let Some((mut op, comparison)) = left {
+        // NOT LIKE, NOT IN, ...
+        if is_keyword(get(tokens, op, -1), "not") {
+            op -= 1;
+        }
+
+        if let Some((start, column)) = column_ref_before(tokens, op) {
+            let comparison = match tokens[op].is_punct("=") && is_set_target(tokens, start) {
+                true => Comparison::Assignment,
+                false => comparison,
+            };
+
+            return Some((column, comparison));
+        }
+    }
+
+    // operators with the column on the right
+    if is_any_punct(tokens.get(idx + 1), COMPARISONS) {
+        return column_ref_after(tokens, idx + 2).map(|column| (column, Comparison::Value));
+    }
+
+    if is_keyword(tokens.get(idx + 1), "is") {
+        let start = if is_keyword(tokens.get(idx + 2), "not") { idx + 3 } else { idx + 2 };
+        return column_ref_after(tokens, start).map(|column| (column, Comparison::NullSafe));
+    }
+
+    None
+}
+
+/// Whether the column reference starting at `start` is the target of an
+/// assignment in the SET clause of an UPDATE or upsert
+fn is_set_target(tokens: &[Token], start: usize) -> bool {
+    // keywords starting a clause after SET, or one which can't contain SET
+    const CLAUSES: &[&str] = &["from", "on", "returning", "select", "values", "where"];
+
+    if !is_keyword(get(tokens, start, -1), "set") && !is_any_punct(get(tokens, start, -1), &[","]) {
+        return false;
+    }
+
+    let mut depth = 0usize;
+
+    for token in tokens[..start].iter().rev() {
+        if token.is_punct(")") {
+            depth += 1;
+        } else if token.is_punct("(") {
+            if depth == 0 {
+                return false;
+            }
+            depth -= 1;
+        } else if depth == 0 && token.is_keyword("set") {
+            return true;
+        } else if depth == 0 && CLAUSES.iter().any(|kw| token.is_keyword(kw)) {
+            return false;
+        }
+    }
+
+    false
+}
+
+/// Parses a column reference ending just before `end`, with the index of its
+/// first token
+fn column_ref_before(tokens: &[Token], end: usize) -> Option<(usize, ColumnRef)> {
+    let mut start = end.checked_sub(1)?;
+
+    while start >= 2 && tokens[start - 1].is_punct(".") && start + 4 >= end {
+        start -= 2;
+    }
+
+    if is_any_punct(get(tokens, start, -1), ARITHMETIC) || is_any_punct(get(tokens, start, -1), &["."]) {
+        return None;
+    }
+
+    column_ref(&tokens[start..end]).map(|column| (start, column))
+}
+
+/// Parses a column reference starting at `start`
+fn column_ref_after(tokens: &[Token], start: usize) -> Option<ColumnRef> {
+    let mut end = start + 1;
+
+    while end + 1 < tokens.len() && tokens[end].is_punct(".") && end < start + 4 {
+        end += 2;
+    }
+
+    if end > tokens.len() {
+        return None;
+    }
+
+    let next = tokens.get(end);
+    if is_any_punct(next, ARITHMETIC) || is_any_punct(next, &["(", "."]) {
+        return None;
+    }
+
+    column_ref(&tokens[start..end])
+}
+
+fn is_limit_operand(tokens: &[Token], idx: usize) -> bool {
+    let before = get(tokens, idx, -1);
+
+    if is_keyword(before, "limit") || is_keyword(before, "offset") {
+        return true;
+    }
+
+    // LIMIT offset, count
+    is_any_punct(before, &[","])
+        && is_operand(get(tokens, idx, -2))
+        && is_keyword(get(tokens, idx, -3), "limit")
+}
+
+/// Finds the column a parameter is inserted into by its position in the
+/// VALUES list of an INSERT
+fn inserted_column(conn: &Connection, tokens: &[Token], scope: &[TableRef], idx: usize) -> Option<ColumnOrigin> {
+    // the parameter must make up the whole value
+    if !is_any_punct(get(tokens, idx, -1), &["(", ","]) || !is_any_punct(tokens.get(idx + 1), &[")", ","]) {
+        return None;
+    }
+
+    let (open, position) = enclosing_list_position(tokens, idx)?;
+
+    // walk back over any earlier rows to VALUES
+    let mut before = open.checked_sub(1)?;
+    while tokens[before].is_punct(",") && tokens.get(before.checked_sub(1)?)?.is_punct(")") {
+        before = matching_open(tokens, before - 1)?.checked_sub(1)?;
+    }
+
+    if !tokens[before].is_keyword("values") {
+        return None;
+    }
+
+    // the INSERT target is the last table named before VALUES
+    let table = scope.iter()
+        .rev()
+        .find(|table| table.token < before)?;
+
+    if !tokens[..table.token].iter().rev().take(3).any(|t| t.is_keyword("into")) {
+        return None;
+    }
+
+    let database = table.database.as_deref().unwrap_or("main");
+
+    let column = match insert_column_list(tokens, table.token + 1, before) {
+        Some(columns) => columns.into_iter().nth(position)?,
+        None => scope::table_column_names(conn, database, &table.name)
+            .into_iter()
+            .nth(position)?,
+    };
+
+    scope::lookup_column(conn, database, &table.name, &column)
+}
+
+/// Returns the index of the opening paren of the list containing `idx`, and
+/// the position of `idx` within it
+fn enclosing_list_position(tokens: &[Token], idx: usize) -> Option<(usize, usize)> {
+    let mut depth = 0usize;
+    let mut position = 0;
+
+    for i in (0..idx).rev() {
+        let token = &tokens[i];
+
+        if token.is_punct(")") {
+            depth += 1;
+        } else if token.is_punct("(") {
+            if depth == 0 {
+                return Some((i, position));
+            }
+            depth -= 1;
+        } else if token.is_punct(",") && depth == 0 {
+            position += 1;
+        }
+    }
+
+    None
+}
+
+fn matching_open(tokens: &[Token], close: usize) -> Option<usize> {
+    let mut depth = 0usize;
+
+    for i in (0..=close).rev() {
+        if tokens[i].is_punct(")") {
+            depth += 1;
+        } else if tokens[i].is_punct("(") {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+
+    None
+}
+
+/// Parses the explicit column list of an INSERT, if between `start` and `end`
+fn insert_column_list(tokens: &[Token], start: usize, end: usize) -> Option<Vec<String>> {
+    let open = (start..end).find(|i| tokens[*i].is_punct("("))?;
+
+    tokens[open + 1..end]
+        .iter()
+        .take_while(|t| !t.is_punct(")"))
+        .filter(|t| !t.is_punct(","))
+        .map(|t| t.ident().map(|name| name.into_owned()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    fn types(sql: &str) -> Vec<ParamType> {
+        let conn = Connection::open_in_memory().unwrap();
+
+        conn.execute_batch("
+            CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT, age INTEGER);
+            CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, title TEXT NOT NULL, \"the body\" TEXT);
+        ").unwrap();
+
+        let stmt = conn.prepare(sql).unwrap();
+        let names = (1..=stmt.parameter_count())
+            .map(|i| stmt.parameter_name(i).map(str::to_owned))
+            .collect::<Vec<_>>();
+
+        param_types(&conn, &tokenize(sql), &names)
+    }
+
+    fn column(table: &str, column: &str) -> ColumnOrigin {
+        ColumnOrigin { database: "main".to_owned(), table: table.to_owned(), column: column.to_owned() }
+    }
+
+    fn compared(table: &str, name: &str) -> ParamType {
+        ParamType::Compared(column(table, name))
+    }
+
+    fn assigned(table: &str, name: &str) -> ParamType {
+        ParamType::Column(column(table, name))
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(
+            types("SELECT * FROM users WHERE name = ? AND ? < age AND email <> ?"),
+            [compared("users", "name"), compared("users", "age"), compared("users", "email")],
+        );
+    }
+
+    #[test]
+    fn in_lists() {
+        assert_eq!(
+            types("SELECT * FROM users WHERE id IN (?, ?) OR age NOT IN (?)"),
+            [compared("users", "id"), compared("users", "id"), compared("users", "age")],
+        );
+    }
+
+    #[test]
+    fn between() {
+        assert_eq!(
+            types("SELECT * FROM users WHERE age BETWEEN ? AND ?"),
+            [compared("users", "age"), compared("users", "age")],
+        );
+    }
+
+    #[test]
+    fn like_and_glob() {
+        assert_eq!(
+            types("SELECT * FROM users WHERE email LIKE ? OR name NOT GLOB ?"),
+            [compared("users", "email"), compared("users", "name")],
+        );
+    }
+
+    #[test]
+    fn is_keeps_nullability() {
+        assert_eq!(
+            types("SELECT * FROM users WHERE email IS ? OR email IS NOT ? OR ? IS age"),
+            [assigned("users", "email"), assigned("users", "email"), assigned("users", "age")],
+        );
+    }
+
+    #[test]
+    fn limit_and_offset() {
+        assert_eq!(types("SELECT * FROM users LIMIT ? OFFSET ?"), [ParamType::Integer, ParamType::Integer]);
+        assert_eq!(types("SELECT * FROM users LIMIT ?, ?"), [ParamType::Integer, ParamType::Integer]);
+    }
+
+    #[test]
+    fn subqueries() {
+        assert_eq!(
+            types("SELECT * FROM users WHERE id IN (SELECT user_id FROM posts WHERE title = ?) AND name = ?"),
+            [compared("posts", "title"), compared("users", "name")],
+        );
+    }
+
+    #[test]
+    fn aliases_and_joins() {
+        assert_eq!(
+            types("SELECT * FROM users u JOIN posts AS p ON p.user_id = u.id WHERE u.name = ? AND p.title = ? AND p.id = ?"),
+            [compared("users", "name"), compared("posts", "title"), compared("posts", "id")],
+        );
+    }
+
+    #[test]
+    fn quoted_identifiers() {
+        assert_eq!(
+            types("SELECT * FROM \"posts\" WHERE \"the body\" = ? AND [title] = ? AND `user_id` = ?"),
+            [compared("posts", "the body"), compared("posts", "title"), compared("posts", "user_id")],
+        );
+    }
+
+    #[test]
+    fn expressions_are_unknown() {
+        assert_eq!(
+            types("SELECT * FROM users WHERE age + 1 = ? AND ? = lower(name)"),
+            [ParamType::Unknown, ParamType::Unknown],
+        );
+    }
+
+    #[test]
+    fn update_set_keeps_nullability() {
+        assert_eq!(
+            types("UPDATE users SET email = ?, age = ? WHERE name = ? AND email = ?"),
+            [assigned("users", "email"), assigned("users", "age"), compared("users", "name"), compared("users", "email")],
+        );
+    }
+
+    #[test]
+    fn insert_values() {
+        assert_eq!(
+            types("INSERT INTO users (name, email) VALUES (?, ?), (?, ?)"),
+            [assigned("users", "name"), assigned("users", "email"), assigned("users", "name"), assigned("users", "email")],
+        );
+
+        assert_eq!(
+            types("INSERT INTO users VALUES (?, ?, ?, ?)"),
+            [assigned("users", "id"), assigned("users", "name"), assigned("users", "email"), assigned("users", "age")],
+        );
+    }
+
+    #[test]
+    fn upsert() {
+        assert_eq!(
+            types("INSERT INTO users (id, name) VALUES (?, ?) ON CONFLICT (id) DO UPDATE SET email = ? WHERE age > ?"),
+            [assigned("users", "id"), assigned("users", "name"), assigned("users", "email"), compared("users", "age")],
+        );
+    }
+
+    #[test]
+    fn named_and_numbered_parameters() {
+        assert_eq!(
+            types("SELECT * FROM users WHERE name = :name OR email = :name OR age = ?3"),
+            [compared("users", "name"), ParamType::Unknown, compared("users", "age")],
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use rusqlite::Connection;
+
+use crate::lexer::Token;
+use crate::scope::{self, column_ref, ColumnOrigin};
+
+/// Resolves the table column each item of the RETURNING clause of an INSERT,
+/// UPDATE or DELETE names, if any. SQLite doesn't report the origin of
+/// returned columns in all versions, so this fills in for it.
+pub fn returning_origins(conn: &Connection, tokens: &[Token]) -> Option<Vec<Option<ColumnOrigin>>> {
+    let returning = find_top_level(tokens, |t| t.is_keyword("returning"))?;
+
+    let statement = find_top_level(tokens, |t| {
+        ["insert", "replace", "update", "delete"].iter().any(|kw| t.is_keyword(kw))
+    })?;
+
+    let target = scope::table_refs(tokens)
+        .into_iter()
+        .find(|table| table.token > statement)?;
+
+    let database = target.database.as_deref().unwrap_or("main");
+    let target = [target.clone()];
+
+    let mut origins = Vec::new();
+
+    for item in split_items(&tokens[returning + 1..]) {
+        if item.len() == 1 && item[0].is_punct("*") {
+            for column in scope::table_column_names(conn, database, &target[0].name) {
+                origins.push(scope::lookup_column(conn, database, &target[0].name, &column));
+            }
+            continue;
+        }
+
+        let origin = column_ref(strip_alias(item))
+            .and_then(|column| column.resolve(conn, &target));
+
+        origins.push(origin);
+    }
+
+    Some(origins)
+}
+
+pub fn find_top_level(tokens: &[Token], pred: impl Fn(&Token) -> bool) -> Option<usize> {
+    let mut depth = 0usize;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0 && pred(token) {
+            return Some(idx);
+        }
+    }
+
+    None
+}
+
+/// Splits a comma separated list at the top level, up to the end of the
+/// statement
+pub fn split_items<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<&'t [Token<'a>]> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0 && token.is_punct(",") {
+            items.push(&tokens[start..idx]);
+            start = idx + 1;
+        } else if depth == 0 && token.is_punct(";") {
+            items.push(&tokens[start..idx]);
+            return items;
+        }
+    }
+
+    items.push(&tokens[start..]);
+    items
+}
+
+fn strip_alias<'t, 'a>(item: &'t [Token<'a>]) -> &'t [Token<'a>] {
+    let len = item.len();
+
+    if len > 2 && item[len - 2].is_keyword("as") {
+        return &item[..len - 2];
+    }
+
+    // bare alias following a column reference
+    if len > 1 && column_ref(&item[..len - 1]).is_some() {
+        return &item[..len - 1];
+    }
+
+    item
+}
@@ -1,15 +1,27 @@
 use std::num::NonZeroUsize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 use rusqlite::types::Type;
 use thiserror::Error;
 
+use crate::affinity::Affinity;
+use crate::annotation::{self, Annotation};
+use crate::infer::{self, ExprType, ValueType};
+use crate::lexer;
+use crate::migrations;
+use crate::params::{self, ParamType};
+use crate::returning;
+use crate::scope::{self, ColumnOrigin};
 use crate::statement::Statement;
 
 pub struct Schema {
     conn: Mutex<Connection>,
+    attached: Vec<String>,
+    // keep the shared in-memory databases alive while they're attached
+    attached_conns: Mutex<Vec<Connection>>,
 }
 
 pub type SqlError = rusqlite::Error;
@@ -22,6 +34,14 @@ pub enum LoadError {
     Sql(rusqlite::Error),
     #[error("loading schema file: {0}")]
     Io(std::io::Error),
+    #[error("attaching database {name}: {error}")]
+    Attach { name: String, error: rusqlite::Error },
+    #[error("executing {}, line {line}: {error}", path.display())]
+    File { path: PathBuf, line: usize, error: rusqlite::Error },
+    #[error("migration file name {} does not start with a version number", .0.display())]
+    MigrationName(PathBuf),
+    #[error("reading schema from database {}: {error}", path.display())]
+    Database { path: PathBuf, error: rusqlite::Error },
 }
 
 #[derive(Error, Debug)]
@@ -32,22 +52,67 @@ pub enum PrepareError {
 
 impl Schema {
     pub fn from_sql(sql: &str) -> Result<Self, LoadError> {
+        Self::load(|conn| conn.execute_batch(sql).map_err(LoadError::Sql))
+    }
+
+    /// Loads the schema from a SQL file, a directory of migrations applied in
+    /// version order, or an existing SQLite database
+    pub fn from_file(path: &Path) -> Result<Self, LoadError> {
+        Self::load(|conn| migrations::apply_path(conn, path))
+    }
+
+    fn load(init: impl FnOnce(&Connection) -> Result<(), LoadError>) -> Result<Self, LoadError> {
         let conn = Connection::open_in_memory()
             .map_err(LoadError::Open)?;
 
-        conn.execute_batch(sql)
-            .map_err(LoadError::Sql)?;
+        init(&conn)?;
 
         let conn = Mutex::new(conn);
 
-        Ok(Schema { conn })
+        Ok(Schema { conn, attached: Vec::new(), attached_conns: Mutex::default() })
     }
 
-    pub fn from_file(path: &Path) -> Result<Self, LoadError> {
-        let sql = std::fs::read_to_string(path)
-            .map_err(LoadError::Io)?;
+    /// Attaches a database with the schema `sql` under `name`, as the
+    /// application does with `ATTACH DATABASE ... AS name`, so its tables can
+    /// be queried as `name.table`
+    pub fn attach_sql(&mut self, name: &str, sql: &str) -> Result<(), LoadError> {
+        self.attach_with(name, |conn| conn.execute_batch(sql).map_err(LoadError::Sql))
+    }
+
+    /// Attaches the schema at `path`, a SQL file, directory of migrations or
+    /// SQLite database
+    pub fn attach_file(&mut self, name: &str, path: &Path) -> Result<(), LoadError> {
+        self.attach_with(name, |conn| migrations::apply_path(conn, path))
+    }
+
+    fn attach_with(&mut self, name: &str, init: impl FnOnce(&Connection) -> Result<(), LoadError>) -> Result<(), LoadError> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let attach_error = |error| LoadError::Attach { name: name.to_owned(), error };
+
+        // the database is created through a connection of its own, as
+        // statements can't be directed to an attached database by default
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let uri = format!("file:sqlitemapper-{}-{}?mode=memory&cache=shared", std::process::id(), id);
+
+        let attached = Connection::open(&uri)
+            .map_err(LoadError::Open)?;
+
+        init(&attached)?;
+
+        self.conn.get_mut().unwrap()
+            .execute("ATTACH DATABASE ?1 AS ?2", [&uri, name])
+            .map_err(attach_error)?;
+
+        self.attached.push(name.to_owned());
+        self.attached_conns.get_mut().unwrap().push(attached);
 
-        Self::from_sql(&sql)
+        Ok(())
+    }
+
+    /// Names of the databases attached to the schema, besides `main`
+    pub fn attached(&self) -> impl Iterator<Item = &str> {
+        self.attached.iter().map(String::as_str)
     }
 
     pub fn prepare(&self, sql: &str) -> Result<QueryInfo, PrepareError> {
@@ -64,70 +129,333 @@ impl Schema {
                 origin_database: stmt.origin_database(i).map(|s| s.to_owned()),
                 origin_table: stmt.origin_table(i).map(|s| s.to_owned()),
                 origin_column: stmt.origin_column(i).map(|s| s.to_owned()),
+                expr_type: None,
+                outer_joined: false,
+                annotation: None,
             };
             columns.push(column);
         }
 
-        Ok(QueryInfo { columns })
+        let readonly = stmt.readonly();
+
+        let param_names = (1..=stmt.parameter_count())
+            .map(|i| stmt.parameter_name(i).map(|s| s.to_owned()))
+            .collect::<Vec<_>>();
+
+        drop(stmt);
+
+        let tokens = lexer::tokenize(sql);
+
+        let missing_origin = columns.iter().any(|col| col.origin_column.is_none());
+
+        if !readonly && missing_origin {
+            let origins = returning::returning_origins(&conn, &tokens)
+                .filter(|origins| origins.len() == columns.len());
+
+            for (column, origin) in columns.iter_mut().zip(origins.into_iter().flatten()) {
+                if let (None, Some(origin)) = (&column.origin_column, origin) {
+                    column.origin_database = Some(origin.database);
+                    column.origin_table = Some(origin.table);
+                    column.origin_column = Some(origin.column);
+                }
+            }
+        }
+
+        let infos = infer::result_columns(&conn, &tokens, columns.len());
+
+        for (column, info) in columns.iter_mut().zip(infos) {
+            // SQLite reports columns selected from views as from the
+            // underlying tables, or nothing for computed columns. Type them
+            // as columns of the view instead, which accounts for joins
+            // within the view.
+            if let Some(origin) = info.origin {
+                if scope::is_view(&conn, &origin.database, &origin.table) {
+                    column.origin_database = Some(origin.database);
+                    column.origin_table = Some(origin.table);
+                    column.origin_column = Some(origin.column);
+                }
+            }
+
+            match column.origin() {
+                None => column.expr_type = info.expr_type,
+                Some(origin) => {
+                    column.outer_joined = info.outer_joined
+                        && scope::column_type(&conn, &origin).is_some_and(|(_, not_null)| not_null);
+                }
+            }
+        }
+
+        let mut annotated = Vec::new();
+
+        for column in &mut columns {
+            let Some(name) = &column.name else {
+                continue;
+            };
+
+            if let Some((plain, annotation)) = annotation::parse(name) {
+                annotated.push((name.clone(), plain.clone()));
+                column.name = Some(plain);
+                column.annotation = Some(annotation);
+            }
+        }
+
+        let stripped_sql = (!annotated.is_empty()).then(|| {
+            let annotated = annotated.iter()
+                .map(|(name, plain)| (name.as_str(), plain.as_str()))
+                .collect::<Vec<_>>();
+
+            annotation::strip(sql, &tokens, &annotated)
+        });
+
+        let param_types = params::param_types(&conn, &tokens, &param_names);
+
+        let params = param_names.into_iter()
+            .zip(param_types)
+            .enumerate()
+            .map(|(i, (name, type_))| QueryParam { index: i + 1, name, type_ })
+            .collect();
+
+        Ok(QueryInfo { columns, params, readonly, stripped_sql })
     }
 
     pub fn tables(&self) -> Result<Vec<String>, rusqlite::Error> {
+        self.tables_in("main")
+    }
+
+    /// Lists the tables of the main or an attached database
+    pub fn tables_in(&self, database: &str) -> Result<Vec<String>, rusqlite::Error> {
+        self.table_list(database, |type_| type_ != "view")
+    }
+
+    pub fn views_in(&self, database: &str) -> Result<Vec<String>, rusqlite::Error> {
+        self.table_list(database, |type_| type_ == "view")
+    }
+
+    fn table_list(&self, database: &str, type_filter: impl Fn(&str) -> bool) -> Result<Vec<String>, rusqlite::Error> {
         let conn = self.conn.lock().unwrap();
 
         struct Table {
             schema: String,
             name: String,
+            type_: String,
         }
 
         let tables = conn
             .prepare("PRAGMA table_list")?
             .query_map([], |row| {
-                Ok(Table { schema: row.get(0)?, name: row.get(1)? })
+                Ok(Table { schema: row.get(0)?, name: row.get(1)?, type_: row.get(2)? })
             })?
             .filter_map(Result::ok)
-            .filter(|t| t.schema == "main")
+            .filter(|t| t.schema == database && type_filter(&t.type_))
             .map(|t| t.name)
             .collect();
 
         Ok(tables)
     }
 
+    /// Lists the columns of a view. SQLite declares no type for columns
+    /// computed by an expression, so their types are worked out from the
+    /// view's SELECT as for a query, and left empty if that's not possible.
+    pub fn view_columns(&self, database: &str, view: &str) -> Result<Vec<TableColumn>, rusqlite::Error> {
+        let mut columns = self.columns_in(database, view)?;
+
+        let sql: String = self.conn.lock().unwrap().query_row(
+            &format!("SELECT sql FROM {}.sqlite_schema WHERE type = 'view' AND name = ?1", scope::quote_ident(database)),
+            [view],
+            |row| row.get(0),
+        )?;
+
+        let Some(select) = view_select(&sql) else {
+            return Ok(columns);
+        };
+
+        let Ok(info) = self.prepare(select) else {
+            return Ok(columns);
+        };
+
+        let mut conn = self.conn.lock().unwrap();
+
+        for (column, result) in columns.iter_mut().zip(info.columns()) {
+            column.not_null = false;
+
+            if let Some(origin) = result.origin() {
+                let origin_type = if scope::is_view(&conn, &origin.database, &origin.table) {
+                    // column of another view, which may be computed
+                    drop(conn);
+                    let type_ = self.view_columns(&origin.database, &origin.table)?
+                        .into_iter()
+                        .find(|col| col.name == origin.column)
+                        .map(|col| (col.type_, col.not_null));
+                    conn = self.conn.lock().unwrap();
+                    type_
+                } else {
+                    scope::column_type(&conn, &origin)
+                };
+
+                if let Some((type_, not_null)) = origin_type {
+                    column.type_ = type_;
+                    column.not_null = not_null && !result.outer_joined;
+                }
+            } else if let Some(expr_type) = result.expr_type {
+                column.type_ = match expr_type.value_type {
+                    ValueType::Integer => "INTEGER",
+                    ValueType::Real => "REAL",
+                    ValueType::Text => "TEXT",
+                    ValueType::Blob => "BLOB",
+                }.to_owned();
+                column.not_null = !expr_type.nullable;
+            } else {
+                column.type_ = String::new();
+            }
+        }
+
+        Ok(columns)
+    }
+
     pub fn columns(&self, table: &str) -> Result<Vec<TableColumn>, rusqlite::Error> {
+        self.columns_in("main", table)
+    }
+
+    pub fn columns_in(&self, database: &str, table: &str) -> Result<Vec<TableColumn>, rusqlite::Error> {
         let conn = self.conn.lock().unwrap();
 
-        let columns = conn
-            .prepare(&format!("PRAGMA table_info({})", table))?
+        let sql = format!("PRAGMA {}.table_info({})", scope::quote_ident(database), scope::quote_ident(table));
+
+        // strictness and WITHOUT ROWID are only reported by table_list
+        let (without_rowid, strict) = conn.query_row(
+            &format!("PRAGMA {}.table_list({})", scope::quote_ident(database), scope::quote_ident(table)),
+            [],
+            |row| Ok((row.get(4)?, row.get(5)?)),
+        ).optional()?.unwrap_or((false, false));
+
+        let table_sql: Option<String> = conn.query_row(
+            &format!("SELECT sql FROM {}.sqlite_schema WHERE type = 'table' AND name = ?1", scope::quote_ident(database)),
+            [table],
+            |row| row.get(0),
+        ).optional()?.flatten();
+
+        let checked_booleans = table_sql.as_deref()
+            .map(boolean_checks)
+            .unwrap_or_default();
+
+        let mut columns = conn
+            .prepare(&sql)?
             .query_map([], |row| {
+                let name: String = row.get(1)?;
+                let type_: String = row.get(2)?;
+                let boolean = ["BOOL", "BOOLEAN"].iter().any(|b| type_.eq_ignore_ascii_case(b))
+                    || checked_booleans.contains(&name);
+
                 Ok(TableColumn {
-                    name: row.get(1)?,
-                    type_: row.get(2)?,
+                    name,
+                    type_,
                     not_null: row.get(3)?,
-                    has_default: row.get_ref(4)?.data_type() == Type::Null,
+                    has_default: row.get_ref(4)?.data_type() != Type::Null,
                     primary_key_part: NonZeroUsize::new(row.get(5)?),
+                    rowid_alias: false,
+                    strict,
+                    without_rowid,
+                    boolean,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
+        // a lone INTEGER primary key of a rowid table is an alias for the rowid
+        let mut primary_key = columns.iter_mut().filter(|col| col.primary_key_part.is_some());
+
+        if let (Some(column), None) = (primary_key.next(), primary_key.next()) {
+            column.rowid_alias = !without_rowid && column.type_.eq_ignore_ascii_case("INTEGER");
+        }
+
         Ok(columns)
     }
 }
 
+/// Finds the SELECT statement in the SQL defining a view
+fn view_select(sql: &str) -> Option<&str> {
+    let tokens = lexer::tokenize(sql);
+
+    let as_ = returning::find_top_level(&tokens, |t| t.is_keyword("as"))?;
+    let select = tokens.get(as_ + 1)?;
+
+    Some(&sql[select.start..])
+}
+
+/// Finds the columns constrained to 0 or 1 by a `CHECK (x IN (0, 1))` in
+/// the SQL creating a table
+fn boolean_checks(sql: &str) -> Vec<String> {
+    let tokens = lexer::tokenize(sql);
+
+    tokens.windows(10)
+        .filter(|t| t[0].is_keyword("check") && t[1].is_punct("(") && t[3].is_keyword("in")
+            && t[4].is_punct("(") && t[6].is_punct(",") && t[8].is_punct(")") && t[9].is_punct(")"))
+        .filter(|t| matches!((t[5].text, t[7].text), ("0", "1") | ("1", "0")))
+        .filter_map(|t| t[2].ident())
+        .map(|name| name.into_owned())
+        .collect()
+}
+
+
 pub struct TableColumn {
     pub name: String,
     pub type_: String,
     pub not_null: bool,
     pub has_default: bool,
     pub primary_key_part: Option<NonZeroUsize>,
+    /// Whether the column is an alias for the rowid, being the INTEGER
+    /// PRIMARY KEY of a table with a rowid, so SQLite assigns it if not given
+    pub rowid_alias: bool,
+    /// Whether the column is in a STRICT table, so its values are always of
+    /// the declared type
+    pub strict: bool,
+    /// Whether the column is in a WITHOUT ROWID table, so rows inserted into
+    /// it are assigned no rowid
+    pub without_rowid: bool,
+    /// Whether the column holds booleans as 0 or 1, being declared BOOLEAN
+    /// or constrained by `CHECK (x IN (0, 1))`
+    pub boolean: bool,
+}
+
+impl TableColumn {
+    pub fn affinity(&self) -> Affinity {
+        Affinity::of(&self.type_)
+    }
+
+    /// Whether the column is declared ANY in a STRICT table, so holds values
+    /// of any type exactly as given. Outside of STRICT tables, ANY is just a
+    /// type name with NUMERIC affinity.
+    pub fn is_any(&self) -> bool {
+        self.strict && self.type_.eq_ignore_ascii_case("ANY")
+    }
 }
 
 pub struct QueryInfo {
-    columns: Vec<ResultColumn>
+    columns: Vec<ResultColumn>,
+    params: Vec<QueryParam>,
+    readonly: bool,
+    stripped_sql: Option<String>,
 }
 
 impl QueryInfo {
     pub fn columns(&self) -> &[ResultColumn] {
         &self.columns
     }
+
+    pub fn params(&self) -> &[QueryParam] {
+        &self.params
+    }
+
+    /// Whether the statement leaves the database unchanged, as reported by
+    /// `sqlite3_stmt_readonly`
+    pub fn is_readonly(&self) -> bool {
+        self.readonly
+    }
+
+    /// The statement's SQL with type annotations removed from column
+    /// aliases, if it had any
+    pub fn stripped_sql(&self) -> Option<&str> {
+        self.stripped_sql.as_deref()
+    }
 }
 
 pub struct ResultColumn {
@@ -136,6 +464,9 @@ pub struct ResultColumn {
     origin_database: Option<String>,
     origin_table: Option<String>,
     origin_column: Option<String>,
+    expr_type: Option<ExprType>,
+    outer_joined: bool,
+    annotation: Option<Annotation>,
 }
 
 impl ResultColumn {
@@ -169,4 +500,180 @@ impl ResultColumn {
     pub fn origin_column(&self) -> Option<&str> {
         self.origin_column.as_deref()
     }
+
+    /// Inferred type of a column computed by an expression, if it has no
+    /// origin column
+    pub fn expr_type(&self) -> Option<&ExprType> {
+        self.expr_type.as_ref()
+    }
+
+    /// Whether the column is from a NOT NULL table column, but on the
+    /// optional side of an outer join so may be NULL in the result
+    pub fn is_outer_joined(&self) -> bool {
+        self.outer_joined
+    }
+
+    fn origin(&self) -> Option<ColumnOrigin> {
+        Some(ColumnOrigin {
+            database: self.origin_database.clone()?,
+            table: self.origin_table.clone()?,
+            column: self.origin_column.clone()?,
+        })
+    }
+
+    /// Type annotation given in the column's alias. The annotation is not
+    /// part of the column's name.
+    pub fn annotation(&self) -> Option<&Annotation> {
+        self.annotation.as_ref()
+    }
+}
+
+pub struct QueryParam {
+    index: usize,
+    name: Option<String>,
+    type_: ParamType,
+}
+
+impl QueryParam {
+    pub fn describe(&self) -> String {
+        match &self.name {
+            Some(name) => format!("Parameter {name}"),
+            None => format!("Parameter ?{}", self.index),
+        }
+    }
+
+    /// Parameter indexes are 1-based, as in SQLite
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn type_(&self) -> &ParamType {
+        &self.type_
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column<'a>(columns: &'a [TableColumn], name: &str) -> &'a TableColumn {
+        columns.iter().find(|col| col.name == name).unwrap()
+    }
+
+    #[test]
+    fn defaults() {
+        let schema = Schema::from_sql("CREATE TABLE t (a TEXT DEFAULT 'x', b TEXT, c INTEGER DEFAULT NULL)").unwrap();
+        let columns = schema.columns("t").unwrap();
+
+        assert!(column(&columns, "a").has_default);
+        assert!(!column(&columns, "b").has_default);
+        // an explicit DEFAULT NULL is still a default
+        assert!(column(&columns, "c").has_default);
+    }
+
+    #[test]
+    fn rowid_aliases() {
+        let schema = Schema::from_sql("
+            CREATE TABLE lower (id integer primary key, name TEXT);
+            CREATE TABLE text_key (code TEXT PRIMARY KEY, label TEXT);
+            CREATE TABLE composite (a INTEGER, b INTEGER, PRIMARY KEY (a, b));
+            CREATE TABLE without (id INTEGER PRIMARY KEY, name TEXT) WITHOUT ROWID;
+        ").unwrap();
+
+        assert!(column(&schema.columns("lower").unwrap(), "id").rowid_alias);
+        assert!(!column(&schema.columns("text_key").unwrap(), "code").rowid_alias);
+        assert!(!column(&schema.columns("composite").unwrap(), "a").rowid_alias);
+        assert!(!column(&schema.columns("without").unwrap(), "id").rowid_alias);
+
+        assert!(column(&schema.columns("without").unwrap(), "name").without_rowid);
+        assert!(!column(&schema.columns("lower").unwrap(), "name").without_rowid);
+    }
+
+    #[test]
+    fn strict_tables() {
+        let schema = Schema::from_sql("
+            CREATE TABLE s (a INTEGER, b ANY) STRICT;
+            CREATE TABLE t (a INTEGER, b ANY);
+        ").unwrap();
+
+        let strict = schema.columns("s").unwrap();
+        assert!(column(&strict, "a").strict);
+        assert!(!column(&strict, "a").is_any());
+        assert!(column(&strict, "b").is_any());
+
+        // ANY is just a type name outside of STRICT tables
+        let loose = schema.columns("t").unwrap();
+        assert!(!column(&loose, "a").strict);
+        assert!(!column(&loose, "b").is_any());
+    }
+
+    #[test]
+    fn boolean_columns() {
+        let schema = Schema::from_sql("
+            CREATE TABLE t (
+                a BOOLEAN,
+                b bool NOT NULL,
+                c INTEGER CHECK (c IN (0, 1)),
+                d INTEGER,
+                e INTEGER CHECK (e IN (0, 2)),
+                CHECK (\"d\" in (1, 0))
+            );
+        ").unwrap();
+
+        let columns = schema.columns("t").unwrap();
+        let booleans = columns.iter().map(|col| (col.name.as_str(), col.boolean)).collect::<Vec<_>>();
+
+        assert_eq!(booleans, [("a", true), ("b", true), ("c", true), ("d", true), ("e", false)]);
+    }
+
+    #[test]
+    fn view_columns() {
+        let schema = Schema::from_sql("
+            CREATE TABLE t (a INTEGER NOT NULL, b TEXT);
+            CREATE TABLE u (a INTEGER NOT NULL, c REAL NOT NULL);
+            CREATE VIEW v AS SELECT t.a, b, t.a * 2 AS doubled, u.c, json_extract(b, '$') AS j FROM t LEFT JOIN u USING (a);
+            CREATE VIEW w AS SELECT doubled, c FROM v;
+        ").unwrap();
+
+        let types = |view| schema.view_columns("main", view).unwrap()
+            .into_iter()
+            .map(|col| (col.name, col.type_, col.not_null))
+            .collect::<Vec<_>>();
+
+        assert_eq!(types("v"), [
+            ("a".to_owned(), "INTEGER".to_owned(), true),
+            ("b".to_owned(), "TEXT".to_owned(), false),
+            ("doubled".to_owned(), "INTEGER".to_owned(), true),
+            ("c".to_owned(), "REAL".to_owned(), false),
+            // not inferrable
+            ("j".to_owned(), String::new(), false),
+        ]);
+
+        // computed columns of other views
+        assert_eq!(types("w"), [
+            ("doubled".to_owned(), "INTEGER".to_owned(), true),
+            ("c".to_owned(), "REAL".to_owned(), false),
+        ]);
+    }
+
+    #[test]
+    fn attached_databases() {
+        let mut schema = Schema::from_sql("CREATE TABLE users (id INTEGER NOT NULL, name TEXT NOT NULL)").unwrap();
+        schema.attach_sql("archive", "CREATE TABLE events (id INTEGER NOT NULL, user_id INTEGER)").unwrap();
+
+        assert_eq!(schema.attached().collect::<Vec<_>>(), ["archive"]);
+        assert!(schema.tables_in("archive").unwrap().contains(&"events".to_owned()));
+        assert!(!schema.tables().unwrap().contains(&"events".to_owned()));
+
+        let info = schema.prepare("SELECT e.id, u.name FROM archive.events e JOIN users u ON u.id = e.user_id").unwrap();
+        let origins = info.columns().iter()
+            .map(|col| (col.origin_database(), col.origin_table()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(origins, [(Some("archive"), Some("events")), (Some("main"), Some("users"))]);
+    }
 }
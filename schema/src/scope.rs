@@ -0,0 +1,565 @@
+use std::ops::Range;
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::lexer::{Token, TokenKind};
+
+/// A table named in a statement, eg. in a FROM or JOIN clause or as the
+/// target of an INSERT, UPDATE or DELETE
+#[derive(Debug, Clone)]
+pub struct TableRef {
+    pub database: Option<String>,
+    /// Name of the table, empty for a subquery
+    pub name: String,
+    pub alias: Option<String>,
+    /// Index of the token naming the table, or opening a subquery
+    pub token: usize,
+    /// Depth of parentheses the table is named within, 0 for tables of the
+    /// outermost statement rather than of a subquery
+    pub depth: usize,
+    /// Whether the table is on the optional side of an outer join, so its
+    /// columns may be NULL in the joined rows
+    pub optional: bool,
+    /// Tokens of the SELECT within the parentheses of a subquery in a FROM
+    /// clause, as in `LEFT JOIN (SELECT ...) AS x`
+    pub subquery: Option<Range<usize>>,
+}
+
+impl TableRef {
+    pub fn is_named(&self, qualifier: &str) -> bool {
+        match &self.alias {
+            Some(alias) => alias.eq_ignore_ascii_case(qualifier),
+            None => self.name.eq_ignore_ascii_case(qualifier),
+        }
+    }
+}
+
+/// A column of a table in the schema, named as SQLite itself names it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnOrigin {
+    pub database: String,
+    pub table: String,
+    pub column: String,
+}
+
+/// A reference to a column by name, as in `col`, `tbl.col` or `db.tbl.col`
+pub struct ColumnRef {
+    pub database: Option<String>,
+    pub qualifier: Option<String>,
+    pub name: String,
+}
+
+impl ColumnRef {
+    pub fn resolve(&self, conn: &Connection, scope: &[TableRef]) -> Option<ColumnOrigin> {
+        self.resolve_table(conn, scope).map(|(_, origin)| origin)
+    }
+
+    /// Like `resolve`, also returning the table in scope the column is from
+    pub fn resolve_table<'s>(&self, conn: &Connection, scope: &'s [TableRef]) -> Option<(&'s TableRef, ColumnOrigin)> {
+        resolve_column(conn, scope, self.database.as_deref(), self.qualifier.as_deref(), &self.name)
+    }
+
+    /// Whether a column which doesn't resolve to a table column may be one
+    /// of a subquery on the optional side of an outer join. Columns a
+    /// subquery computes or renames can't be traced to its tables.
+    pub fn maybe_outer_joined(&self, scope: &[TableRef]) -> bool {
+        scope.iter()
+            .filter(|table| table.subquery.is_some() && table.optional)
+            .any(|table| self.qualifier.as_deref().is_none_or(|qualifier| table.is_named(qualifier)))
+    }
+}
+
+/// Parses a column reference making up the whole of `tokens`
+pub fn column_ref(tokens: &[Token]) -> Option<ColumnRef> {
+    if tokens.len().is_multiple_of(2) || tokens.len() > 5 {
+        return None;
+    }
+
+    if !tokens.iter().skip(1).step_by(2).all(|t| t.is_punct(".")) {
+        return None;
+    }
+
+    let mut names = tokens.iter()
+        .step_by(2)
+        .map(column_ref_part)
+        .collect::<Option<Vec<_>>>()?;
+
+    let name = names.pop()?;
+    let qualifier = names.pop();
+    let database = names.pop();
+
+    Some(ColumnRef { database, qualifier, name })
+}
+
+fn column_ref_part(token: &Token) -> Option<String> {
+    // keywords which are values rather than names
+    const LITERALS: &[&str] = &["null", "true", "false", "current_date", "current_time", "current_timestamp"];
+
+    if LITERALS.iter().any(|kw| token.is_keyword(kw)) {
+        return None;
+    }
+
+    token.ident().map(|name| name.into_owned())
+}
+
+// words that end a table reference rather than giving it a bare alias
+const NOT_ALIASES: &[&str] = &[
+    "as", "cross", "default", "do", "except", "full", "group", "having",
+    "indexed", "inner", "intersect", "join", "left", "limit", "natural", "not",
+    "on", "order", "outer", "returning", "right", "select", "set", "union",
+    "using", "values", "where", "window",
+];
+
+pub fn table_refs(tokens: &[Token]) -> Vec<TableRef> {
+    let mut refs: Vec<TableRef> = Vec::new();
+    let mut idx = 0;
+
+    // paren depth, and the first of `refs` in the FROM clause open at each
+    // depth, which a RIGHT or FULL join makes optional
+    let mut depth = 0;
+    let mut clause_starts = vec![0];
+
+    while idx < tokens.len() {
+        let token = &tokens[idx];
+        idx += 1;
+
+        if token.is_punct("(") {
+            depth += 1;
+            clause_starts.push(refs.len());
+        } else if token.is_punct(")") && depth > 0 {
+            depth -= 1;
+            clause_starts.pop();
+        } else if token.is_keyword("from") {
+            clause_starts[depth] = refs.len();
+        }
+
+        if token.is_keyword("join") {
+            let join = join_kind(&tokens[..idx - 1]);
+
+            if matches!(join, JoinKind::Right | JoinKind::Full) {
+                let subqueries = refs[clause_starts[depth]..]
+                    .iter_mut()
+                    .filter(|t| t.depth == depth)
+                    .filter_map(|table| {
+                        table.optional = true;
+                        table.subquery.clone()
+                    })
+                    .collect::<Vec<_>>();
+
+                // along with the tables of subqueries
+                for table in refs.iter_mut().filter(|t| subqueries.iter().any(|s| s.contains(&t.token))) {
+                    table.optional = true;
+                }
+            }
+
+            if let Some((mut table, next)) = parse_from_item(tokens, idx) {
+                table.optional = matches!(join, JoinKind::Left | JoinKind::Full);
+                push_table(&mut refs, tokens, table, depth);
+                idx = next;
+            }
+        } else if token.is_keyword("from") {
+            // comma separated list of tables, as in FROM a, b
+            while let Some((table, next)) = parse_from_item(tokens, idx) {
+                push_table(&mut refs, tokens, table, depth);
+                idx = next;
+
+                if !tokens.get(idx).is_some_and(|t| t.is_punct(",")) {
+                    break;
+                }
+
+                idx += 1;
+            }
+        } else if token.is_keyword("update") {
+            if tokens.get(idx).is_some_and(|t| t.is_keyword("or")) {
+                idx += 2;
+            }
+
+            // DO UPDATE SET in an upsert clause names no table
+            if tokens.get(idx).is_some_and(|t| t.is_keyword("set")) {
+                continue;
+            }
+
+            if let Some((table, next)) = parse_table_ref(tokens, idx, true) {
+                push_table(&mut refs, tokens, table, depth);
+                idx = next;
+            }
+        } else if token.is_keyword("into") {
+            if let Some((table, next)) = parse_table_ref(tokens, idx, false) {
+                push_table(&mut refs, tokens, table, depth);
+                idx = next;
+            }
+        }
+    }
+
+    refs
+}
+
+/// Adds a table named at `depth` to the scope, followed by the tables named
+/// within it if it's a subquery
+fn push_table(refs: &mut Vec<TableRef>, tokens: &[Token], mut table: TableRef, depth: usize) {
+    table.depth = depth;
+
+    let subquery = table.subquery.clone();
+    let optional = table.optional;
+
+    refs.push(table);
+
+    let Some(subquery) = subquery else {
+        return;
+    };
+
+    let offset = subquery.start;
+
+    for mut inner in table_refs(&tokens[subquery]) {
+        inner.token += offset;
+        inner.depth += depth + 1;
+        inner.optional |= optional;
+        inner.subquery = inner.subquery.map(|range| range.start + offset..range.end + offset);
+        refs.push(inner);
+    }
+}
+
+/// Parses a table or a subquery in a FROM clause, and its alias if any
+fn parse_from_item(tokens: &[Token], idx: usize) -> Option<(TableRef, usize)> {
+    if !tokens.get(idx).is_some_and(|t| t.is_punct("(")) {
+        return parse_table_ref(tokens, idx, true);
+    }
+
+    // a parenthesized join, as in FROM (a JOIN b), isn't a subquery
+    let is_subquery = tokens.get(idx + 1)
+        .is_some_and(|t| t.is_keyword("select") || t.is_keyword("with") || t.is_keyword("values"));
+
+    if !is_subquery {
+        return None;
+    }
+
+    let mut depth = 0usize;
+    let close = idx + tokens[idx..].iter().position(|t| {
+        if t.is_punct("(") {
+            depth += 1;
+        } else if t.is_punct(")") {
+            depth -= 1;
+        }
+        depth == 0
+    })?;
+
+    let (alias, next) = parse_alias(tokens, close + 1, true);
+
+    let table = TableRef {
+        database: None,
+        name: String::new(),
+        alias,
+        token: idx,
+        depth: 0,
+        optional: false,
+        subquery: Some(idx + 1..close),
+    };
+
+    Some((table, next))
+}
+
+/// Parses a table name and its alias if any. Only the target of an INSERT
+/// may not have a bare alias, and may be followed by a column list.
+fn parse_table_ref(tokens: &[Token], idx: usize, bare_alias: bool) -> Option<(TableRef, usize)> {
+    let (database, name, idx) = parse_qualified_name(tokens, idx)?;
+    let token = idx - 1;
+
+    // table valued function rather than a table
+    if bare_alias && tokens.get(idx).is_some_and(|t| t.is_punct("(")) {
+        return None;
+    }
+
+    let (alias, idx) = parse_alias(tokens, idx, bare_alias);
+
+    Some((TableRef { database, name, alias, token, depth: 0, optional: false, subquery: None }, idx))
+}
+
+/// Parses `AS alias`, or a bare alias where allowed, returning the index
+/// after it
+fn parse_alias(tokens: &[Token], idx: usize, bare_alias: bool) -> (Option<String>, usize) {
+    if tokens.get(idx).is_some_and(|t| t.is_keyword("as")) {
+        let alias = tokens.get(idx + 1).and_then(Token::ident).map(|a| a.into_owned());
+        return (alias, idx + 2);
+    }
+
+    if let Some(tok) = tokens.get(idx).filter(|_| bare_alias) {
+        let is_alias = match tok.kind {
+            TokenKind::QuotedIdent => true,
+            TokenKind::Ident => !NOT_ALIASES.iter().any(|kw| tok.is_keyword(kw)),
+            _ => false,
+        };
+
+        if is_alias {
+            return (tok.ident().map(|a| a.into_owned()), idx + 1);
+        }
+    }
+
+    (None, idx)
+}
+
+enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full,
+}
+
+/// Finds the kind of join from the operator preceding a JOIN keyword, as in
+/// `NATURAL LEFT OUTER JOIN`
+fn join_kind(before: &[Token]) -> JoinKind {
+    let mut before = before.iter().rev();
+    let mut token = before.next();
+
+    if token.is_some_and(|t| t.is_keyword("outer")) {
+        token = before.next();
+    }
+
+    match token {
+        Some(t) if t.is_keyword("left") => JoinKind::Left,
+        Some(t) if t.is_keyword("right") => JoinKind::Right,
+        Some(t) if t.is_keyword("full") => JoinKind::Full,
+        _ => JoinKind::Inner,
+    }
+}
+
+/// Parses `name` or `database.name`, returning the index after the name
+fn parse_qualified_name(tokens: &[Token], idx: usize) -> Option<(Option<String>, String, usize)> {
+    let first = tokens.get(idx)?.ident()?.into_owned();
+
+    if tokens.get(idx + 1).is_some_and(|t| t.is_punct(".")) {
+        let second = tokens.get(idx + 2)?.ident()?.into_owned();
+        Some((Some(first), second, idx + 3))
+    } else {
+        Some((None, first, idx + 1))
+    }
+}
+
+/// Resolves a possibly qualified column reference against the tables in
+/// scope, returning None if it doesn't name exactly one table column
+pub fn resolve_column<'s>(
+    conn: &Connection,
+    scope: &'s [TableRef],
+    database: Option<&str>,
+    qualifier: Option<&str>,
+    column: &str,
+) -> Option<(&'s TableRef, ColumnOrigin)> {
+    let mut found = None;
+
+    for table in scope {
+        if let Some(qualifier) = qualifier {
+            if !table.is_named(qualifier) {
+                continue;
+            }
+        }
+
+        if let (Some(database), Some(table_database)) = (database, &table.database) {
+            if !database.eq_ignore_ascii_case(table_database) {
+                continue;
+            }
+        }
+
+        let table_database = table.database.as_deref().unwrap_or("main");
+
+        let Some(origin) = lookup_column(conn, table_database, &table.name, column) else {
+            continue;
+        };
+
+        if found.is_some() {
+            // ambiguous
+            return None;
+        }
+
+        found = Some((table, origin));
+    }
+
+    found
+}
+
+/// Looks up a column by name, case insensitively as SQLite does
+pub fn lookup_column(conn: &Connection, database: &str, table: &str, column: &str) -> Option<ColumnOrigin> {
+    let table = canonical_table_name(conn, database, table)?;
+
+    table_column_names(conn, database, &table)
+        .into_iter()
+        .find(|name| name.eq_ignore_ascii_case(column))
+        .map(|column| ColumnOrigin { database: database.to_owned(), table, column })
+}
+
+/// Counts the columns of a subquery in a FROM clause by preparing it on its
+/// own, which fails if it refers to common table expressions
+pub fn subquery_column_count(conn: &Connection, tokens: &[Token]) -> Option<usize> {
+    let sql = tokens.iter().map(|t| t.text).collect::<Vec<_>>().join(" ");
+
+    conn.prepare(&sql).ok().map(|stmt| stmt.column_count())
+}
+
+pub fn table_column_names(conn: &Connection, database: &str, table: &str) -> Vec<String> {
+    let sql = format!("PRAGMA {}.table_info({})", quote_ident(database), quote_ident(table));
+
+    conn.prepare(&sql)
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get(1))?
+                .collect::<Result<Vec<String>, _>>()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the declared type of a column and whether it is NOT NULL
+pub fn column_type(conn: &Connection, origin: &ColumnOrigin) -> Option<(String, bool)> {
+    let sql = format!("PRAGMA {}.table_info({})", quote_ident(&origin.database), quote_ident(&origin.table));
+
+    conn.prepare(&sql)
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get::<_, String>(1)?, row.get(2)?, row.get(3)?)))?
+                .collect::<Result<Vec<(String, String, bool)>, _>>()
+        })
+        .ok()?
+        .into_iter()
+        .find(|(name, _, _)| *name == origin.column)
+        .map(|(_, type_, not_null)| (type_, not_null))
+}
+
+pub fn is_view(conn: &Connection, database: &str, name: &str) -> bool {
+    let sql = format!("SELECT 1 FROM {}.sqlite_schema WHERE type = 'view' AND name = ?1", quote_ident(database));
+
+    conn.query_row(&sql, [name], |_| Ok(()))
+        .optional()
+        .is_ok_and(|found| found.is_some())
+}
+
+fn canonical_table_name(conn: &Connection, database: &str, table: &str) -> Option<String> {
+    let sql = format!(
+        "SELECT name FROM {}.sqlite_schema WHERE type IN ('table', 'view') AND name = ?1 COLLATE NOCASE",
+        quote_ident(database),
+    );
+
+    conn.query_row(&sql, [table], |row| row.get(0))
+        .optional()
+        .ok()
+        .flatten()
+}
+
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    type Table = (Option<String>, String, Option<String>, usize, bool);
+
+    /// (database, name, alias, depth, optional) of each table named in `sql`
+    fn tables(sql: &str) -> Vec<Table> {
+        table_refs(&tokenize(sql))
+            .into_iter()
+            .map(|t| (t.database, t.name, t.alias, t.depth, t.optional))
+            .collect()
+    }
+
+    fn table(name: &str, alias: Option<&str>, depth: usize, optional: bool) -> Table {
+        (None, name.to_owned(), alias.map(str::to_owned), depth, optional)
+    }
+
+    #[test]
+    fn aliases() {
+        assert_eq!(
+            tables("SELECT * FROM a, b AS x, c y, \"d\" \"z\" WHERE 1"),
+            [table("a", None, 0, false), table("b", Some("x"), 0, false), table("c", Some("y"), 0, false), table("d", Some("z"), 0, false)],
+        );
+    }
+
+    #[test]
+    fn joins() {
+        assert_eq!(
+            tables("SELECT * FROM a JOIN b ON 1 LEFT JOIN c USING (id) RIGHT OUTER JOIN d"),
+            [table("a", None, 0, true), table("b", None, 0, true), table("c", None, 0, true), table("d", None, 0, false)],
+        );
+
+        assert_eq!(
+            tables("SELECT * FROM a NATURAL LEFT OUTER JOIN b"),
+            [table("a", None, 0, false), table("b", None, 0, true)],
+        );
+    }
+
+    #[test]
+    fn subqueries() {
+        assert_eq!(
+            tables("SELECT * FROM a WHERE id IN (SELECT a_id FROM b RIGHT JOIN c)"),
+            [table("a", None, 0, false), table("b", None, 1, true), table("c", None, 1, false)],
+        );
+    }
+
+    #[test]
+    fn subqueries_in_from() {
+        let refs = table_refs(&tokenize("SELECT * FROM a LEFT JOIN (SELECT * FROM b JOIN c) AS x ON 1"));
+        let subquery = |table: &TableRef| table.subquery.as_ref().map(|range| range.len());
+
+        assert_eq!(
+            refs.iter().map(|t| (t.name.as_str(), t.alias.as_deref(), t.depth, t.optional, subquery(t))).collect::<Vec<_>>(),
+            [
+                ("a", None, 0, false, None),
+                ("", Some("x"), 0, true, Some(6)),
+                // the tables of a subquery are as optional as the subquery
+                ("b", None, 1, true, None),
+                ("c", None, 1, true, None),
+            ],
+        );
+
+        assert_eq!(refs[2].token, 10);
+
+        assert_eq!(
+            tables("SELECT * FROM (SELECT * FROM a) x RIGHT JOIN b"),
+            [table("", Some("x"), 0, true), table("a", None, 1, true), table("b", None, 0, false)],
+        );
+    }
+
+    #[test]
+    fn statement_targets() {
+        assert_eq!(tables("INSERT INTO a (x) VALUES (1)"), [table("a", None, 0, false)]);
+        assert_eq!(tables("UPDATE OR IGNORE a SET x = 1"), [table("a", None, 0, false)]);
+        assert_eq!(tables("DELETE FROM a AS b WHERE x = 1"), [table("a", Some("b"), 0, false)]);
+        assert_eq!(
+            tables("INSERT INTO a VALUES (1) ON CONFLICT DO UPDATE SET x = 2"),
+            [table("a", None, 0, false)],
+        );
+    }
+
+    #[test]
+    fn attached_databases_and_functions() {
+        assert_eq!(
+            tables("SELECT * FROM other.a, json_each(a.x)"),
+            [(Some("other".to_owned()), "a".to_owned(), None, 0, false)],
+        );
+    }
+
+    #[test]
+    fn column_refs() {
+        let column = column_ref(&tokenize("db.\"t\".[c]")).unwrap();
+        assert_eq!(
+            (column.database.as_deref(), column.qualifier.as_deref(), column.name.as_str()),
+            (Some("db"), Some("t"), "c"),
+        );
+
+        assert!(column_ref(&tokenize("NULL")).is_none());
+        assert!(column_ref(&tokenize("a.")).is_none());
+        assert!(column_ref(&tokenize("'a'")).is_none());
+    }
+
+    #[test]
+    fn resolves_columns_case_insensitively() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE Users (Id INTEGER); CREATE TABLE posts (id INTEGER, title TEXT)").unwrap();
+
+        let scope = table_refs(&tokenize("SELECT * FROM users u JOIN posts p"));
+        let resolve = |sql| column_ref(&tokenize(sql)).unwrap().resolve(&conn, &scope);
+
+        assert_eq!(resolve("U.ID"), Some(ColumnOrigin { database: "main".into(), table: "Users".into(), column: "Id".into() }));
+        assert_eq!(resolve("title"), Some(ColumnOrigin { database: "main".into(), table: "posts".into(), column: "title".into() }));
+        // ambiguous without a qualifier
+        assert_eq!(resolve("id"), None);
+        // a table is only named by its alias
+        assert_eq!(resolve("posts.id"), None);
+    }
+}
@@ -20,7 +20,7 @@ fn from_int(u: c_int) -> usize {
 }
 
 unsafe fn maybe_cstr<'a>(ptr: *const c_char) -> Option<&'a str> {
-    if ptr == ptr::null() {
+    if ptr.is_null() {
         return None;
     }
 
@@ -80,6 +80,25 @@ impl<'a> Statement<'a> {
             maybe_cstr(ffi::sqlite3_column_name(self.raw.ptr, to_int(idx)))
         }
     }
+
+    pub fn readonly(&self) -> bool {
+        unsafe {
+            ffi::sqlite3_stmt_readonly(self.raw.ptr) != 0
+        }
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        unsafe {
+            from_int(ffi::sqlite3_bind_parameter_count(self.raw.ptr))
+        }
+    }
+
+    /// Parameter indexes are 1-based, as in SQLite
+    pub fn parameter_name(&self, idx: usize) -> Option<&str> {
+        unsafe {
+            maybe_cstr(ffi::sqlite3_bind_parameter_name(self.raw.ptr, to_int(idx)))
+        }
+    }
 }
 
 pub struct RawStatement<'a> {
@@ -0,0 +1,5 @@
+CREATE TABLE events (
+    id INTEGER NOT NULL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    detail TEXT
+);
@@ -0,0 +1,12 @@
+use std::path::Path;
+
+// the schema! and query! macros in this crate's tests read the schema from
+// here, rather than the example's set in .cargo/config.toml
+fn main() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    println!("cargo:rustc-env=SQLITEMAPPER_SCHEMA={}", dir.join("schema").display());
+    println!("cargo:rustc-env=SQLITEMAPPER_ATTACH=archive={}", dir.join("attach/archive.sql").display());
+    println!("cargo:rerun-if-changed=schema");
+    println!("cargo:rerun-if-changed=attach");
+}
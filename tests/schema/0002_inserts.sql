@@ -0,0 +1,21 @@
+-- primary keys which are and aren't assigned by SQLite when not inserted
+
+CREATE TABLE codes (
+    code TEXT NOT NULL PRIMARY KEY,
+    label TEXT
+);
+
+CREATE TABLE items (
+    id integer not null primary key,
+    name TEXT NOT NULL
+);
+
+CREATE TABLE settings (
+    id INTEGER NOT NULL PRIMARY KEY,
+    value TEXT NOT NULL
+) WITHOUT ROWID;
+
+CREATE TABLE tokens (
+    token TEXT NOT NULL PRIMARY KEY DEFAULT (lower(hex(randomblob(8)))),
+    note TEXT NOT NULL
+);
@@ -0,0 +1,5 @@
+CREATE VIEW post_counts AS
+SELECT users.id, users.name, count(posts.id) AS posts, max(posts.title) AS last_title
+FROM users
+LEFT JOIN posts ON posts.user_id = users.id
+GROUP BY users.id;
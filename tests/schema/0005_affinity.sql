@@ -0,0 +1,7 @@
+CREATE TABLE measurements (
+    id BIGINT NOT NULL PRIMARY KEY,
+    label VARCHAR(255) NOT NULL,
+    value DOUBLE NOT NULL,
+    amount DECIMAL(10, 2),
+    data
+);
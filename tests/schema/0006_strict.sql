@@ -0,0 +1,5 @@
+CREATE TABLE readings (
+    id INTEGER NOT NULL PRIMARY KEY,
+    value REAL NOT NULL,
+    raw ANY
+) STRICT;
@@ -0,0 +1,5 @@
+CREATE TABLE flags (
+    id INTEGER NOT NULL PRIMARY KEY,
+    enabled BOOLEAN NOT NULL,
+    visible INTEGER CHECK (visible IN (0, 1))
+);
@@ -0,0 +1,4 @@
+CREATE TABLE appointments (
+    id INTEGER NOT NULL PRIMARY KEY,
+    starts_at DATETIME NOT NULL
+);
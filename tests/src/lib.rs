@@ -0,0 +1,74 @@
+//! Schema shared by the tests of the macros, which are compiled against the
+//! migrations in `schema/`, with `attach/archive.sql` attached as `archive`
+
+use std::fmt::{self, Display};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use rusqlite::Connection;
+
+sqlitemapper::schema!{
+    pub mod schema {
+        mod appointments {
+            type starts_at = crate::Date;
+        }
+    }
+}
+
+/// A date as `YYYY-MM-DD` text, for columns of a type with a domain type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl FromStr for Date {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, '-');
+        let mut part = || parts.next().unwrap_or_default();
+
+        Ok(Date { year: part().parse()?, month: part().parse()?, day: part().parse()? })
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+const MIGRATIONS: &[&str] = &[
+    include_str!("../schema/0001_init.sql"),
+    include_str!("../schema/0002_inserts.sql"),
+    include_str!("../schema/0003_posts.sql"),
+    include_str!("../schema/0004_views.sql"),
+    include_str!("../schema/0005_affinity.sql"),
+    include_str!("../schema/0006_strict.sql"),
+    include_str!("../schema/0007_booleans.sql"),
+    include_str!("../schema/0008_dates.sql"),
+];
+
+const ARCHIVE: &str = include_str!("../attach/archive.sql");
+
+/// Applies the migrations of the main database to `conn`
+pub fn migrate(conn: &Connection) {
+    for migration in MIGRATIONS {
+        conn.execute_batch(migration).unwrap();
+    }
+}
+
+/// Opens an in-memory database with the schema applied
+pub fn open() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    migrate(&conn);
+
+    conn.execute_batch("ATTACH ':memory:' AS archive").unwrap();
+
+    // unqualified names in the archive's schema create tables in main
+    conn.execute_batch(&ARCHIVE.replace("CREATE TABLE ", "CREATE TABLE archive.")).unwrap();
+
+    conn
+}
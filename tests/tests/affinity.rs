@@ -0,0 +1,52 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema, Date};
+
+#[test]
+fn declared_types_map_by_affinity() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO measurements (id, label, value, amount, data) VALUES (?, ?, ?, ?, ?)")
+        .bind((1, "width", 2.5, Some("10.50"), Some(vec![1u8, 2])))
+        .execute(&mut conn)
+        .unwrap();
+
+    let record = query!(schema, "SELECT * FROM measurements")
+        .bind(())
+        .query_one::<schema::measurements::Record>(&mut conn)
+        .unwrap();
+
+    assert_eq!(record.id, 1i64);
+    assert_eq!(record.label, "width");
+    assert_eq!(record.value, 2.5f64);
+    // NUMERIC affinity converts text which is a number
+    assert_eq!(record.amount.as_deref(), Some("10.5"));
+    assert_eq!(record.data, Some(vec![1, 2]));
+}
+
+#[test]
+fn numeric_columns_convert_through_text() {
+    let mut conn = open();
+    let date = Date { year: 2024, month: 2, day: 29 };
+
+    query!(schema, "INSERT INTO appointments (id, starts_at) VALUES (?, ?)")
+        .bind((1, date))
+        .execute(&mut conn)
+        .unwrap();
+
+    let stored: String = conn.query_row("SELECT starts_at FROM appointments", [], |row| row.get(0)).unwrap();
+    assert_eq!(stored, "2024-02-29");
+
+    let record = query!(schema, "SELECT * FROM appointments")
+        .bind(())
+        .query_one::<schema::appointments::Record>(&mut conn)
+        .unwrap();
+
+    assert_eq!(record.starts_at, date);
+
+    let row = query!(schema, "SELECT id, starts_at FROM appointments WHERE starts_at = ?")
+        .bind((date,))
+        .query_optional::<(i64, Date)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(row, Some((1, date)));
+}
@@ -0,0 +1,43 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+fn insert_users(conn: &mut rusqlite::Connection) {
+    query!(schema, "INSERT INTO users (name, age) VALUES ('alice', 30), ('bob', NULL)")
+        .bind(())
+        .execute(conn)
+        .unwrap();
+}
+
+#[test]
+fn nullability_overrides() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let (age, name) = query!(schema, r#"SELECT age AS "age!", name AS "name?" FROM users WHERE age IS NOT NULL"#)
+        .bind(())
+        .query_one::<(i64, Option<String>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!((age, name.as_deref()), (30, Some("alice")));
+}
+
+#[test]
+fn rust_types() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let ages = query!(schema, r#"SELECT json_extract('{"a": 1}', '$.a') AS "a: i64", age AS "age?: i64" FROM users ORDER BY id"#)
+        .bind(())
+        .query_all::<(i64, Option<i64>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(ages, [(1, Some(30)), (1, None)]);
+}
+
+#[test]
+fn annotations_are_stripped_from_column_names() {
+    let conn = open();
+
+    let stmt = conn.prepare(query!(schema, r#"SELECT count(*) AS "n: i64" FROM users"#).sql()).unwrap();
+    assert_eq!(stmt.column_names(), ["n"]);
+}
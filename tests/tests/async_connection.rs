@@ -0,0 +1,67 @@
+use sqlitemapper::{query, AsyncConnection, Error};
+use sqlitemapper_tests::{open, schema};
+
+#[tokio::test]
+async fn runs_typed_queries_off_the_runtime() {
+    let conn = AsyncConnection::new(open());
+
+    query!(schema, "INSERT INTO users (name, age) VALUES (?, ?)")
+        .bind(("alice", Some(30)))
+        .execute_async(&conn)
+        .await
+        .unwrap();
+
+    let names = query!(schema, "SELECT name FROM users WHERE age > ?")
+        .bind((20,))
+        .query_all_async::<(String,)>(&conn)
+        .await
+        .unwrap();
+
+    assert_eq!(names, [("alice".to_owned(),)]);
+
+    let missing = query!(schema, "SELECT name FROM users WHERE name = ?")
+        .bind(("bob",))
+        .query_optional_async::<(String,)>(&conn)
+        .await
+        .unwrap();
+
+    assert_eq!(missing, None);
+}
+
+#[tokio::test]
+async fn clones_share_the_connection() {
+    let conn = AsyncConnection::new(open());
+    let clone = conn.clone();
+
+    query!(schema, "INSERT INTO users (name) VALUES ('alice')")
+        .bind(())
+        .execute_async(&clone)
+        .await
+        .unwrap();
+
+    drop(clone);
+
+    let count = query!(schema, "SELECT count(*) AS n FROM users")
+        .bind(())
+        .query_scalar_async(&conn)
+        .await
+        .unwrap();
+
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn errors_are_returned_from_the_connection_thread() {
+    let conn = AsyncConnection::new(open());
+
+    let result = query!(schema, "SELECT name FROM users")
+        .bind(())
+        .query_one_async::<(String,)>(&conn)
+        .await;
+
+    assert!(matches!(result, Err(Error::NoRows)));
+
+    // a panicking call drops its reply, leaving the handle unusable
+    let result = conn.call(|_| -> Result<(), Error> { panic!("call panicked") }).await;
+    assert!(matches!(result, Err(Error::ConnectionClosed)));
+}
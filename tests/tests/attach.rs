@@ -0,0 +1,42 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn attached_tables_have_nested_modules() {
+    let mut conn = open();
+
+    schema::archive::events::insert(schema::archive::events::NewRecord {
+        kind: "login".to_owned(),
+        detail: None,
+    }).execute(&mut conn).unwrap();
+
+    let events = query!(schema, "SELECT * FROM archive.events WHERE kind = ?")
+        .bind(("login",))
+        .query_all::<schema::archive::events::Record>(&mut conn)
+        .unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].kind.as_str(), events[0].detail.as_deref()), ("login", None));
+}
+
+#[test]
+fn attached_and_main_tables_join() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO users (id, name) VALUES (1, 'alice')")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    query!(schema, "INSERT INTO archive.events (kind, detail) VALUES ('login', ?)")
+        .bind((Some("1"),))
+        .execute(&mut conn)
+        .unwrap();
+
+    let rows = query!(schema, "SELECT u.name, e.kind FROM archive.events e JOIN users u ON u.id = e.detail")
+        .bind(())
+        .query_all::<(String, String)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(rows, [("alice".to_owned(), "login".to_owned())]);
+}
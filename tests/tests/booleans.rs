@@ -0,0 +1,71 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn boolean_columns_read_and_bind_bools() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO flags (id, enabled, visible) VALUES (?, ?, ?)")
+        .bind((1, true, Some(false)))
+        .execute(&mut conn)
+        .unwrap();
+
+    query!(schema, "INSERT INTO flags (id, enabled, visible) VALUES (?, ?, ?)")
+        .bind((2, false, None::<bool>))
+        .execute(&mut conn)
+        .unwrap();
+
+    let stored = conn
+        .prepare("SELECT enabled, visible FROM flags ORDER BY id")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<Vec<(i64, Option<i64>)>, _>>()
+        .unwrap();
+
+    assert_eq!(stored, [(1, Some(0)), (0, None)]);
+
+    let records = query!(schema, "SELECT * FROM flags ORDER BY id")
+        .bind(())
+        .query_all::<schema::flags::Record>(&mut conn)
+        .unwrap()
+        .into_iter()
+        .map(|record| (record.enabled, record.visible))
+        .collect::<Vec<_>>();
+
+    assert_eq!(records, [(true, Some(false)), (false, None)]);
+}
+
+#[test]
+fn boolean_columns_compare_with_bools() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO flags (id, enabled) VALUES (1, 1), (2, 0)")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    let ids = query!(schema, "SELECT id FROM flags WHERE enabled = ?")
+        .bind((false,))
+        .query_all::<(i64,)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(ids, [(2,)]);
+}
+
+#[test]
+fn out_of_range_booleans_are_errors() {
+    let mut conn = open();
+
+    // the BOOLEAN column itself is unconstrained
+    query!(schema, "INSERT INTO flags (id, enabled) VALUES (1, 2)")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    let result = query!(schema, "SELECT enabled FROM flags")
+        .bind(())
+        .query_one::<(bool,)>(&mut conn);
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,50 @@
+use sqlitemapper::query;
+use sqlitemapper::execute::ExecuteResult;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn execute_reports_rows_affected_and_rowid() {
+    let mut conn = open();
+
+    let insert = query!(schema, "INSERT INTO users (name) VALUES (?)");
+
+    let first = insert.bind(("alice",)).execute(&mut conn).unwrap();
+    let second = insert.bind(("bob",)).execute(&mut conn).unwrap();
+
+    assert_eq!(first, ExecuteResult { rows_affected: 1, last_insert_rowid: 1 });
+    assert_eq!(second, ExecuteResult { rows_affected: 1, last_insert_rowid: 2 });
+
+    let updated = query!(schema, "UPDATE users SET age = ?")
+        .bind((Some(20),))
+        .execute(&mut conn)
+        .unwrap();
+
+    assert_eq!(updated.rows_affected, 2);
+
+    let deleted = query!(schema, "DELETE FROM users WHERE name = ?")
+        .bind(("nobody",))
+        .execute(&mut conn)
+        .unwrap();
+
+    assert_eq!(deleted.rows_affected, 0);
+}
+
+#[test]
+fn prepared_execute_runs_repeatedly() {
+    let mut conn = open();
+
+    {
+        let mut insert = query!(schema, "INSERT INTO users (name) VALUES (?)").prepare(&conn).unwrap();
+
+        for name in ["a", "b", "c"] {
+            insert.execute((name,)).unwrap();
+        }
+    }
+
+    let count = query!(schema, "SELECT count(*) FROM users")
+        .bind(())
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(count, 3);
+}
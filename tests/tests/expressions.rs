@@ -0,0 +1,43 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn expression_columns_are_typed() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO users (name, age) VALUES ('alice', 30), ('bob', NULL)")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    let row = query!(schema, "SELECT count(*), total(age), sum(age), upper(max(name)) FROM users")
+        .bind(())
+        .query_one::<(i64, f64, Option<i64>, Option<String>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(row, (2, 30.0, Some(30), Some("BOB".to_owned())));
+
+    let names = query!(schema, "SELECT name, length(name) + 1 AS len FROM users WHERE age IS NULL")
+        .bind(())
+        .query_all::<(String, i64)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(names, [("bob".to_owned(), 4)]);
+}
+
+#[test]
+fn math_functions_may_be_null() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO users (name, age) VALUES ('alice', -4)")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    let root = query!(schema, "SELECT sqrt(age) FROM users WHERE age IS NOT NULL")
+        .bind(())
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(root, None::<f64>);
+}
@@ -0,0 +1,67 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn rowid_alias_is_assigned() {
+    let mut conn = open();
+
+    let id = schema::items::insert(schema::items::NewRecord { name: "widget".to_owned() })
+        .execute(&mut conn)
+        .unwrap();
+
+    let name = query!(schema, "SELECT name FROM items WHERE id = ?")
+        .bind((id,))
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(name, "widget");
+}
+
+#[test]
+fn defaulted_key_is_assigned() {
+    let mut conn = open();
+
+    schema::tokens::insert(schema::tokens::NewRecord { note: "first".to_owned() })
+        .execute(&mut conn)
+        .unwrap();
+
+    let token = query!(schema, "SELECT token FROM tokens WHERE note = 'first'")
+        .bind(())
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(token.len(), 16);
+}
+
+#[test]
+fn text_key_is_inserted() {
+    let mut conn = open();
+
+    schema::codes::insert(schema::codes::Record { code: "GB".to_owned(), label: Some("United Kingdom".to_owned()) })
+        .execute(&mut conn)
+        .unwrap();
+
+    let label = query!(schema, "SELECT label FROM codes WHERE code = ?")
+        .bind(("GB",))
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(label.as_deref(), Some("United Kingdom"));
+}
+
+#[test]
+fn without_rowid_key_is_inserted() {
+    let mut conn = open();
+
+    // the table has no rowid, so none is returned
+    let () = schema::settings::insert(schema::settings::Record { id: 7, value: "on".to_owned() })
+        .execute(&mut conn)
+        .unwrap();
+
+    let value = query!(schema, "SELECT value FROM settings WHERE id = 7")
+        .bind(())
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(value, "on");
+}
@@ -0,0 +1,54 @@
+use std::fmt::{self, Display};
+
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+struct Email(&'static str);
+
+impl Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}@example.com", self.0)
+    }
+}
+
+struct Age(u8);
+
+impl From<Age> for i64 {
+    fn from(age: Age) -> i64 {
+        age.0.into()
+    }
+}
+
+#[test]
+fn domain_types_bind_through_their_conversions() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO users (name, email, age) VALUES (?, ?, ?)")
+        .bind(("alice", Some(Email("alice")), Some(Age(30))))
+        .execute(&mut conn)
+        .unwrap();
+
+    let user = query!(schema, "SELECT email, age FROM users WHERE email = ? AND age = ?")
+        .bind((Email("alice"), Age(30)))
+        .query_one::<(Option<String>, Option<i64>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(user, (Some("alice@example.com".to_owned()), Some(30)));
+}
+
+#[test]
+fn bytes_bind_to_blob_columns() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO measurements (id, label, value, data) VALUES (1, 'x', 0, ?)")
+        .bind((Some(*b"\x00\x01"),))
+        .execute(&mut conn)
+        .unwrap();
+
+    let data = query!(schema, "SELECT data FROM measurements")
+        .bind(())
+        .query_one::<(Option<Vec<u8>>,)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(data, (Some(vec![0, 1]),));
+}
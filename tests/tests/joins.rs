@@ -0,0 +1,82 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+fn insert_rows(conn: &mut rusqlite::Connection) {
+    query!(schema, "INSERT INTO users (id, name) VALUES (1, 'alice'), (2, 'bob')")
+        .bind(())
+        .execute(conn)
+        .unwrap();
+
+    query!(schema, "INSERT INTO posts (user_id, title) VALUES (1, 'hello')")
+        .bind(())
+        .execute(conn)
+        .unwrap();
+}
+
+#[test]
+fn left_join_columns_are_nullable() {
+    let mut conn = open();
+    insert_rows(&mut conn);
+
+    let rows = query!(schema, "SELECT users.name, posts.title FROM users LEFT JOIN posts ON posts.user_id = users.id ORDER BY users.id")
+        .bind(())
+        .query_all::<(String, Option<String>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(rows, [("alice".to_owned(), Some("hello".to_owned())), ("bob".to_owned(), None)]);
+}
+
+#[test]
+fn right_join_makes_earlier_tables_nullable() {
+    let mut conn = open();
+    insert_rows(&mut conn);
+
+    let rows = query!(schema, "SELECT p.title, u.name FROM posts p RIGHT JOIN users u ON p.user_id = u.id ORDER BY u.id")
+        .bind(())
+        .query_all::<(Option<String>, String)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(rows, [(Some("hello".to_owned()), "alice".to_owned()), (None, "bob".to_owned())]);
+}
+
+#[test]
+fn inner_join_columns_keep_nullability() {
+    let mut conn = open();
+    insert_rows(&mut conn);
+
+    let rows = query!(schema, "SELECT users.name, posts.title FROM users JOIN posts ON posts.user_id = users.id")
+        .bind(())
+        .query_all::<(String, String)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(rows, [("alice".to_owned(), "hello".to_owned())]);
+}
+
+#[test]
+fn star_from_optional_table() {
+    let mut conn = open();
+    insert_rows(&mut conn);
+
+    let rows = query!(schema, "SELECT users.name, posts.* FROM users LEFT JOIN posts ON posts.user_id = users.id WHERE users.id = 2")
+        .bind(())
+        .query_all::<(String, Option<i64>, Option<i64>, Option<String>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(rows, [("bob".to_owned(), None, None, None)]);
+}
+
+#[test]
+fn left_join_subquery_columns_are_nullable() {
+    let mut conn = open();
+    insert_rows(&mut conn);
+
+    let rows = query!(schema, "SELECT users.name, x.title, x.* FROM users LEFT JOIN (SELECT user_id, title FROM posts) AS x ON x.user_id = users.id ORDER BY users.id")
+        .bind(())
+        .query_all::<(String, Option<String>, Option<i64>, Option<String>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(rows, [
+        ("alice".to_owned(), Some("hello".to_owned()), Some(1), Some("hello".to_owned())),
+        ("bob".to_owned(), None, None, None),
+    ]);
+}
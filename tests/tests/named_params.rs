@@ -0,0 +1,112 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+fn insert_users(conn: &mut rusqlite::Connection) {
+    for (name, age) in [("alice", 30), ("bob", 40)] {
+        query!(schema, "INSERT INTO users (name, age) VALUES (:name, :age)", name, age = Some(age))
+            .execute(conn)
+            .unwrap();
+    }
+}
+
+#[test]
+fn unbound_queries_bind_tuples_in_index_order() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let find = query!(schema, "SELECT name FROM users WHERE age > :min AND age < :max", _);
+
+    let names = find.bind((20, 35)).query_all::<(String,)>(&mut conn).unwrap();
+    assert_eq!(names, [("alice".to_owned(),)]);
+
+    let mut prepared = find.prepare(&conn).unwrap();
+    let names = prepared.query_iter::<(String,), _>((35, 50)).unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(names, [("bob".to_owned(),)]);
+}
+
+#[test]
+fn arguments_bind_by_name() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let name = query!(schema, "SELECT name FROM users WHERE age < :max AND name <> :name", name = "bob", max = 50)
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(name, "alice");
+}
+
+#[test]
+fn missing_arguments_are_captured() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let max = 35;
+    let name = query!(schema, "SELECT name FROM users WHERE age < :max AND age > :min", min = 0)
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(name, "alice");
+}
+
+#[test]
+fn all_parameters_are_captured_without_arguments() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let (min, max) = (35, 50);
+    let name = query!(schema, "SELECT name FROM users WHERE age > :min AND age < :max")
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(name, "bob");
+}
+
+#[test]
+fn rest_argument_takes_fields() {
+    struct Filter {
+        min: i64,
+        max: i64,
+    }
+
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let filter = Filter { min: 35, max: 50 };
+    let name = query!(schema, "SELECT name FROM users WHERE age > :min AND age < :max", ..filter)
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(name, "bob");
+}
+
+#[test]
+fn repeated_names_bind_once() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let count = query!(schema, "SELECT count(*) FROM users WHERE age >= :age OR age + 10 = :age", _)
+        .bind((40,))
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn unbound_executes() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let rename = query!(schema, "UPDATE users SET name = :new WHERE name = :old", _);
+    rename.bind(("carol", "alice")).execute(&mut conn).unwrap();
+
+    let names = query!(schema, "SELECT name FROM users ORDER BY name")
+        .bind(())
+        .query_all::<(String,)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(names, [("bob".to_owned(),), ("carol".to_owned(),)]);
+}
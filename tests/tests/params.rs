@@ -0,0 +1,69 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn compared_params_are_not_null() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO users (name, email) VALUES (?, ?)")
+        .bind(("alice", Some("alice@example.com")))
+        .execute(&mut conn)
+        .unwrap();
+
+    // email is nullable, but comparing it with NULL never matches, so the
+    // parameter takes a plain value
+    let name = query!(schema, "SELECT name FROM users WHERE email = ?")
+        .bind(("alice@example.com",))
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(name, "alice");
+}
+
+#[test]
+fn assigned_and_is_params_keep_nullability() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO users (name, email) VALUES (?, ?)")
+        .bind(("alice", None::<String>))
+        .execute(&mut conn)
+        .unwrap();
+
+    let count = query!(schema, "SELECT count(*) FROM users WHERE email IS ?")
+        .bind((None::<String>,))
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(count, 1);
+
+    query!(schema, "UPDATE users SET email = ? WHERE name = ?")
+        .bind((Some("alice@example.com"), "alice"))
+        .execute(&mut conn)
+        .unwrap();
+
+    let email = query!(schema, "SELECT email FROM users WHERE name = ?")
+        .bind(("alice",))
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(email.as_deref(), Some("alice@example.com"));
+}
+
+#[test]
+fn limit_params_are_integers() {
+    let mut conn = open();
+
+    for name in ["a", "b", "c"] {
+        query!(schema, "INSERT INTO users (name) VALUES (?)")
+            .bind((name,))
+            .execute(&mut conn)
+            .unwrap();
+    }
+
+    let names = query!(schema, "SELECT name FROM users ORDER BY name LIMIT ? OFFSET ?")
+        .bind((2, 1))
+        .query_all::<(String,)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(names, [("b".to_owned(),), ("c".to_owned(),)]);
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use sqlitemapper::{query, Pool};
+use sqlitemapper_tests::{migrate, schema};
+use tempfile::TempDir;
+
+fn pool(dir: &TempDir, max_readers: usize) -> Pool {
+    let pool = Pool::builder(dir.path().join("test.db"))
+        .max_readers(max_readers)
+        .init(|conn| conn.pragma_update(None, "foreign_keys", true))
+        .open()
+        .unwrap();
+
+    migrate(&pool.write().unwrap());
+    pool
+}
+
+#[test]
+fn readers_see_committed_writes() {
+    let dir = TempDir::new().unwrap();
+    let pool = pool(&dir, 4);
+
+    let mut writer = pool.write().unwrap();
+    assert!(!writer.is_read_only());
+
+    let journal_mode: String = writer.pragma_query_value(None, "journal_mode", |row| row.get(0)).unwrap();
+    assert_eq!(journal_mode, "wal");
+
+    query!(schema, "INSERT INTO users (name) VALUES ('alice')")
+        .bind(())
+        .execute(&mut writer)
+        .unwrap();
+
+    let mut reader = pool.read().unwrap();
+    assert!(reader.is_read_only());
+
+    let names = query!(schema, "SELECT name FROM users")
+        .bind(())
+        .query_all::<(String,)>(&mut reader)
+        .unwrap();
+
+    assert_eq!(names, [("alice".to_owned(),)]);
+}
+
+#[test]
+fn readers_cannot_write() {
+    let dir = TempDir::new().unwrap();
+    let pool = pool(&dir, 4);
+
+    let result = query!(schema, "INSERT INTO users (name) VALUES ('alice')")
+        .bind(())
+        .execute(&mut pool.read().unwrap());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn readers_run_alongside_an_open_write_transaction() {
+    let dir = TempDir::new().unwrap();
+    let pool = pool(&dir, 4);
+
+    let writer = pool.write().unwrap();
+    writer.execute_batch("BEGIN IMMEDIATE; INSERT INTO users (name) VALUES ('alice')").unwrap();
+
+    // the uncommitted insert isn't visible, but doesn't block the reader
+    let count = query!(schema, "SELECT count(*) AS n FROM users")
+        .bind(())
+        .query_scalar(&mut pool.read().unwrap())
+        .unwrap();
+
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn init_hooks_run_on_each_connection_in_order() {
+    let dir = TempDir::new().unwrap();
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let pool = Pool::builder(dir.path().join("test.db"))
+        .init({
+            let calls = calls.clone();
+            move |conn| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                conn.pragma_update(None, "foreign_keys", true)
+            }
+        })
+        .init(|conn| {
+            let foreign_keys: bool = conn.pragma_query_value(None, "foreign_keys", |row| row.get(0))?;
+            assert!(foreign_keys);
+            Ok(())
+        })
+        .open()
+        .unwrap();
+
+    let (first, second) = (pool.read().unwrap(), pool.read().unwrap());
+    drop((first, second));
+
+    // reuses an idle reader
+    drop(pool.read().unwrap());
+
+    // the writer opened by `open`, and two readers
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn checkouts_wait_for_a_connection_to_be_returned() {
+    let dir = TempDir::new().unwrap();
+    let pool = pool(&dir, 1);
+
+    let reader = pool.read().unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let waiting = thread::spawn({
+        let pool = pool.clone();
+        move || tx.send(pool.read().map(|_| ()).is_ok()).unwrap()
+    });
+
+    assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+    drop(reader);
+
+    assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+    waiting.join().unwrap();
+}
@@ -0,0 +1,71 @@
+use sqlitemapper::{query, query_as, FromRow};
+use sqlitemapper_tests::{open, schema};
+
+#[derive(Debug, PartialEq, FromRow)]
+struct User {
+    id: i64,
+    name: String,
+    email: Option<String>,
+}
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Named {
+    r#type: String,
+}
+
+fn insert_users(conn: &mut rusqlite::Connection) {
+    query!(schema, "INSERT INTO users (name, email) VALUES ('alice', 'alice@example.com'), ('bob', NULL)")
+        .bind(())
+        .execute(conn)
+        .unwrap();
+}
+
+#[test]
+fn reads_structs_by_field() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let users = query_as!(User, schema, "SELECT id, name, email FROM users ORDER BY id")
+        .bind(())
+        .query_all::<User>(&mut conn)
+        .unwrap();
+
+    assert_eq!(users, [
+        User { id: 1, name: "alice".to_owned(), email: Some("alice@example.com".to_owned()) },
+        User { id: 2, name: "bob".to_owned(), email: None },
+    ]);
+}
+
+#[test]
+fn aliases_name_fields() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let named = query_as!(Named, schema, "SELECT name AS type FROM users WHERE id = ?")
+        .bind((2,))
+        .query_one::<Named>(&mut conn)
+        .unwrap();
+
+    assert_eq!(named, Named { r#type: "bob".to_owned() });
+}
+
+#[test]
+fn accepts_named_parameters() {
+    let mut conn = open();
+    insert_users(&mut conn);
+
+    let name = "alice";
+    let user = query_as!(User, schema, "SELECT id, name, email FROM users WHERE name = :name", name)
+        .query_optional::<User>(&mut conn)
+        .unwrap();
+
+    assert_eq!(user.map(|user| user.id), Some(1));
+}
+
+#[test]
+fn derive_lists_fields_in_order() {
+    use sqlitemapper::from_row::FromRowFields;
+
+    assert_eq!(User::FIELDS, ["id", "name", "email"]);
+    assert_eq!(Named::FIELDS, ["type"]);
+}
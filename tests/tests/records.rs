@@ -0,0 +1,45 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn select_star_reads_records() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO users (name, age) VALUES ('alice', 30)")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    let record = query!(schema, "SELECT * FROM users")
+        .bind(())
+        .query_one::<schema::users::Record>(&mut conn)
+        .unwrap();
+
+    assert_eq!(
+        (record.id, record.name.as_str(), record.email, record.age),
+        (1, "alice", None, Some(30)),
+    );
+}
+
+#[test]
+fn qualified_star_reads_records_of_one_table() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO codes (code, label) VALUES ('GB', 'United Kingdom'), ('FR', NULL)")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    query!(schema, "INSERT INTO users (name, email) VALUES ('alice', 'GB')")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    let codes = query!(schema, "SELECT codes.* FROM users JOIN codes ON codes.code = users.email")
+        .bind(())
+        .query_all::<schema::codes::Record>(&mut conn)
+        .unwrap();
+
+    assert_eq!(codes.len(), 1);
+    assert_eq!((codes[0].code.as_str(), codes[0].label.as_deref()), ("GB", Some("United Kingdom")));
+}
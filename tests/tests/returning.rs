@@ -0,0 +1,38 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn insert_returning_reads_table_columns() {
+    let mut conn = open();
+
+    let (id, name, email) = query!(schema, "INSERT INTO users (name) VALUES (?) RETURNING id, name, email")
+        .bind(("alice",))
+        .query_one::<(i64, String, Option<String>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!((id, name.as_str(), email), (1, "alice", None));
+}
+
+#[test]
+fn update_and_delete_returning() {
+    let mut conn = open();
+
+    query!(schema, "INSERT INTO users (name, age) VALUES ('alice', 30), ('bob', 40)")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    let ages = query!(schema, "UPDATE users SET age = age + 1 RETURNING name, age")
+        .bind(())
+        .query_all::<(String, Option<i64>)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(ages, [("alice".to_owned(), Some(31)), ("bob".to_owned(), Some(41))]);
+
+    let deleted = query!(schema, "DELETE FROM users WHERE name = ? RETURNING users.id")
+        .bind(("bob",))
+        .query_optional::<(i64,)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(deleted, Some((2,)));
+}
@@ -0,0 +1,47 @@
+use rusqlite::types::Value;
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn any_columns_keep_values_as_given() {
+    let mut conn = open();
+
+    for (id, raw) in [(1, Value::Text("1".to_owned())), (2, Value::Integer(1)), (3, Value::Blob(vec![1]))] {
+        query!(schema, "INSERT INTO readings (id, value, raw) VALUES (?, ?, ?)")
+            .bind((id, 1.5, Some(raw)))
+            .execute(&mut conn)
+            .unwrap();
+    }
+
+    let raw = query!(schema, "SELECT raw FROM readings ORDER BY id")
+        .bind(())
+        .query_all::<(Option<Value>,)>(&mut conn)
+        .unwrap()
+        .into_iter()
+        .map(|(raw,)| raw)
+        .collect::<Vec<_>>();
+
+    assert_eq!(raw, [
+        Some(Value::Text("1".to_owned())),
+        Some(Value::Integer(1)),
+        Some(Value::Blob(vec![1])),
+    ]);
+}
+
+#[test]
+fn strict_columns_read_declared_types() {
+    let mut conn = open();
+
+    // SQLite converts the integer on insert into a STRICT REAL column
+    query!(schema, "INSERT INTO readings (id, value) VALUES (1, 2)")
+        .bind(())
+        .execute(&mut conn)
+        .unwrap();
+
+    let record = query!(schema, "SELECT * FROM readings")
+        .bind(())
+        .query_one::<schema::readings::Record>(&mut conn)
+        .unwrap();
+
+    assert_eq!((record.id, record.value, record.raw), (1, 2.0, None));
+}
@@ -0,0 +1,138 @@
+use sqlitemapper::{query, transaction, Connection, Error, Executor, Transaction, TransactionBehavior};
+use sqlitemapper_tests::{open, schema};
+
+fn user_names(conn: &mut Connection) -> Vec<(String,)> {
+    query!(schema, "SELECT name FROM users ORDER BY id")
+        .bind(())
+        .query_all(conn)
+        .unwrap()
+}
+
+fn insert_user(tx: &mut Transaction, name: &str) {
+    query!(schema, "INSERT INTO users (name) VALUES (?)")
+        .bind((name,))
+        .execute(tx)
+        .unwrap();
+}
+
+/// Inserts a post by a user who doesn't exist, which with deferred foreign
+/// keys only fails once the transaction commits
+fn insert_orphan_post(tx: &mut Transaction) {
+    query!(schema, "INSERT INTO posts (user_id, title) VALUES (?, 'orphan')")
+        .bind((404,))
+        .execute(tx)
+        .unwrap();
+}
+
+fn open_with_foreign_keys() -> Connection {
+    let conn = open();
+    conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+    conn
+}
+
+#[test]
+fn commits_and_rolls_back() {
+    let mut conn = open();
+
+    let mut tx = Transaction::begin(&mut conn, TransactionBehavior::Immediate).unwrap();
+    insert_user(&mut tx, "alice");
+    tx.commit().unwrap();
+
+    let mut tx = Transaction::begin(&mut conn, TransactionBehavior::Deferred).unwrap();
+    insert_user(&mut tx, "bob");
+    tx.rollback().unwrap();
+
+    // dropped without committing
+    let mut tx = Transaction::begin(&mut conn, TransactionBehavior::Deferred).unwrap();
+    insert_user(&mut tx, "carol");
+    drop(tx);
+
+    assert_eq!(user_names(&mut conn), [("alice".to_owned(),)]);
+}
+
+#[test]
+fn savepoints_roll_back_independently() {
+    let mut conn = open();
+
+    let result = transaction(&mut conn, TransactionBehavior::Deferred, |tx| {
+        insert_user(tx, "alice");
+
+        let result = tx.with_savepoint(|sp| {
+            insert_user(sp, "bob");
+            Err::<(), _>(Error::NoRows)
+        });
+        assert!(result.is_err());
+
+        tx.with_savepoint(|sp| {
+            insert_user(sp, "carol");
+            Ok::<_, Error>(())
+        })
+    });
+
+    assert!(result.is_ok());
+    assert_eq!(user_names(&mut conn), [("alice".to_owned(),), ("carol".to_owned(),)]);
+}
+
+#[test]
+fn failed_commits_roll_back() {
+    let mut conn = open_with_foreign_keys();
+
+    let mut tx = Transaction::begin(&mut conn, TransactionBehavior::Deferred).unwrap();
+    tx.connection().execute_batch("PRAGMA defer_foreign_keys = ON").unwrap();
+    insert_user(&mut tx, "alice");
+
+    // the savepoint is released, but its changes are still part of the
+    // transaction which fails to commit
+    let mut sp = tx.savepoint().unwrap();
+    insert_orphan_post(&mut sp);
+    sp.commit().unwrap();
+
+    assert!(tx.commit().is_err());
+
+    assert!(conn.is_autocommit());
+    assert_eq!(user_names(&mut conn), []);
+
+    // the connection can begin a new transaction
+    let tx = Transaction::begin(&mut conn, TransactionBehavior::Deferred).unwrap();
+    tx.commit().unwrap();
+}
+
+#[test]
+fn failed_commits_in_transaction_roll_back() {
+    let mut conn = open_with_foreign_keys();
+
+    let result = transaction(&mut conn, TransactionBehavior::Immediate, |tx| {
+        tx.connection().execute_batch("PRAGMA defer_foreign_keys = ON")?;
+        insert_user(tx, "alice");
+        insert_orphan_post(tx);
+        Ok::<_, Error>(())
+    });
+
+    assert!(result.is_err());
+    assert!(conn.is_autocommit());
+    assert_eq!(user_names(&mut conn), []);
+}
+
+#[test]
+fn failed_savepoint_releases_are_errors() {
+    let mut conn = open();
+
+    let mut tx = Transaction::begin(&mut conn, TransactionBehavior::Deferred).unwrap();
+    insert_user(&mut tx, "alice");
+
+    let mut sp = tx.savepoint().unwrap();
+    insert_user(&mut sp, "bob");
+
+    let mut inner = sp.savepoint().unwrap();
+    insert_user(&mut inner, "carol");
+    inner.commit().unwrap();
+
+    // releasing the outer savepoint out from under `sp` makes its release
+    // fail, after which dropping it can't roll back either
+    sp.connection().execute_batch("RELEASE sqlitemapper_savepoint_1").unwrap();
+    assert!(sp.commit().is_err());
+
+    tx.commit().unwrap();
+
+    assert_eq!(user_names(&mut conn), [("alice".to_owned(),), ("bob".to_owned(),), ("carol".to_owned(),)]);
+}
@@ -0,0 +1,42 @@
+use sqlitemapper::verify::SchemaDifference;
+use sqlitemapper_tests::{open, schema};
+
+#[test]
+fn schema_matches_the_migrated_database() {
+    let conn = open();
+
+    let diff = schema::verify(&conn).unwrap();
+
+    assert!(diff.is_empty(), "{}", diff);
+    assert_eq!(schema::FINGERPRINT, sqlitemapper::verify::fingerprint(schema::TABLES));
+}
+
+#[test]
+fn tables_and_views_of_all_databases_are_verified() {
+    let tables = schema::TABLES.iter()
+        .map(|table| (table.database, table.name, table.view))
+        .collect::<Vec<_>>();
+
+    assert!(tables.contains(&("main", "users", false)));
+    assert!(tables.contains(&("main", "post_counts", true)));
+    assert!(tables.contains(&("archive", "events", false)));
+}
+
+#[test]
+fn drift_is_reported() {
+    let conn = open();
+
+    conn.execute_batch("
+        DROP VIEW post_counts;
+        ALTER TABLE posts DROP COLUMN title;
+        DETACH archive;
+    ").unwrap();
+
+    let diff = schema::verify(&conn).unwrap();
+
+    assert_eq!(diff.differences, [
+        SchemaDifference::MissingColumn { database: "main".to_owned(), table: "posts".to_owned(), column: "title".to_owned() },
+        SchemaDifference::MissingView { database: "main".to_owned(), view: "post_counts".to_owned() },
+        SchemaDifference::MissingTable { database: "archive".to_owned(), table: "events".to_owned() },
+    ]);
+}
@@ -0,0 +1,47 @@
+use sqlitemapper::query;
+use sqlitemapper_tests::{open, schema};
+
+fn insert_rows(conn: &mut rusqlite::Connection) {
+    query!(schema, "INSERT INTO users (id, name) VALUES (1, 'alice'), (2, 'bob')")
+        .bind(())
+        .execute(conn)
+        .unwrap();
+
+    query!(schema, "INSERT INTO posts (user_id, title) VALUES (1, 'a'), (1, 'b')")
+        .bind(())
+        .execute(conn)
+        .unwrap();
+}
+
+#[test]
+fn views_read_as_records() {
+    let mut conn = open();
+    insert_rows(&mut conn);
+
+    let counts = query!(schema, "SELECT * FROM post_counts ORDER BY id")
+        .bind(())
+        .query_all::<schema::post_counts::Record>(&mut conn)
+        .unwrap();
+
+    let counts = counts.into_iter()
+        .map(|record| (record.id, record.name, record.posts, record.last_title))
+        .collect::<Vec<_>>();
+
+    assert_eq!(counts, [
+        (1, "alice".to_owned(), 2, Some("b".to_owned())),
+        (2, "bob".to_owned(), 0, None),
+    ]);
+}
+
+#[test]
+fn view_columns_are_typed_in_queries() {
+    let mut conn = open();
+    insert_rows(&mut conn);
+
+    let name = query!(schema, "SELECT name FROM post_counts WHERE posts = ?")
+        .bind((0,))
+        .query_scalar(&mut conn)
+        .unwrap();
+
+    assert_eq!(name, "bob");
+}